@@ -9,6 +9,31 @@ use genanki_rs::{Deck, Field, Model, Note, Template};
 use std::io::Cursor;
 use std::path::Path;
 use syntect::highlighting::ThemeSet;
+use tracing::warn;
+
+pub(crate) const FRONT_TEMPLATE: &str = include_str!("../assets/templates/front.html");
+pub(crate) const BACK_TEMPLATE: &str = include_str!("../assets/templates/back.html");
+pub(crate) const CSS: &str = include_str!("../assets/templates/style.css");
+pub(crate) const TEMPLATE_NAME: &str = "LeetCode";
+/// Model id of the note type with the fields below. Anki matches imported note types by id, so it
+/// must change whenever the fields change.
+pub const MODEL_ID: i64 = 1307111928;
+/// Model id of the earlier note type, without the `Hints` and `Editorial` fields.
+const LEGACY_MODEL_ID: i64 = 1307111927;
+/// Fields of the LeetCode note type, in order. `ID` identifies the problem of a note.
+pub(crate) const FIELD_NAMES: [&str; 11] = [
+    "ID",
+    "Title",
+    "TitleSlug",
+    "Difficulty",
+    "Description",
+    "Tags",
+    "TagSlugs",
+    "Submission",
+    "Note",
+    "Hints",
+    "Editorial",
+];
 
 pub struct AnkiDeckManager<'c, 'p> {
     pub deck: Deck,
@@ -23,17 +48,13 @@ impl<'c, 'p> AnkiDeckManager<'c, 'p> {
         anki_config: &AnkiConfig,
         syntect_adapter: &'p SyntectAdapter,
     ) -> Result<Self, AnkiError> {
-        let front_template = include_str!("../assets/templates/front.html");
-        let back_template = include_str!("../assets/templates/back.html");
-        let css = include_str!("../assets/templates/style.css");
-
         let model = new_model(
-            anki_config.model.id,
+            model_id(anki_config.model.id),
             &anki_config.model.name,
-            "LeetCode",
-            front_template,
-            back_template,
-            css,
+            TEMPLATE_NAME,
+            FRONT_TEMPLATE,
+            BACK_TEMPLATE,
+            CSS,
         );
 
         let comrak_plugins = set_up_comrak_plugins(syntect_adapter);
@@ -93,17 +114,7 @@ fn new_model(
     Model::new(
         model_id,
         model_name,
-        vec![
-            Field::new("ID"),
-            Field::new("Title"),
-            Field::new("TitleSlug"),
-            Field::new("Difficulty"),
-            Field::new("Description"),
-            Field::new("Tags"),
-            Field::new("TagSlugs"),
-            Field::new("Submission"),
-            Field::new("Note"),
-        ],
+        FIELD_NAMES.iter().map(|name| Field::new(name)).collect(),
         vec![
             Template::new(template_name)
                 .qfmt(front_template)
@@ -119,54 +130,101 @@ fn new_note(
     comrak_options: &Options,
     comrak_plugins: &Plugins,
 ) -> Result<Note, Box<genanki_rs::Error>> {
-    let tags_str = problem
-        .description
-        .topic_tags
-        .iter()
-        .map(|tag| tag.name.as_str())
-        .collect::<Vec<_>>()
-        .join(";");
-    let tag_slugs = problem
-        .description
-        .topic_tags
-        .iter()
-        .map(|tag| tag.slug.as_str())
-        .collect::<Vec<_>>();
-    let tag_slugs_str = tag_slugs.join(";");
-
-    let code_htmls = problem
-        .submissions
-        .iter()
-        .map(|code| {
-            markdown_to_html_with_plugins(
-                &code.to_markdown().unwrap(),
-                comrak_options,
-                comrak_plugins,
-            )
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
+    let content = NoteContent::new(problem, comrak_options, comrak_plugins);
 
     Note::new_with_options(
         model,
-        vec![
-            &problem.description.question_frontend_id,
-            &problem.description.question_title,
-            &problem.description.question_title_slug,
-            &problem.description.difficulty.to_string(),
-            &problem.description.content,
-            &tags_str,
-            &tag_slugs_str,
-            &code_htmls,
-            "",
-        ],
+        content.fields.iter().map(String::as_str).collect(),
         None,
-        Some(tag_slugs),
+        Some(content.tags.iter().map(String::as_str).collect()),
         Some(&problem.description.question_frontend_id),
     )
     .map_err(Box::new)
 }
 
+/// The rendered note of a problem: its field values in the order of `FIELD_NAMES` and its tags.
+pub(crate) struct NoteContent {
+    pub fields: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+impl NoteContent {
+    pub fn new(
+        problem: &LeetCodeProblem,
+        comrak_options: &Options,
+        comrak_plugins: &Plugins,
+    ) -> Self {
+        let tags_str = problem
+            .description
+            .topic_tags
+            .iter()
+            .map(|tag| tag.name.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+        let tag_slugs = problem
+            .description
+            .topic_tags
+            .iter()
+            .map(|tag| tag.slug.as_str())
+            .collect::<Vec<_>>();
+        let tag_slugs_str = tag_slugs.join(";");
+        let note_tags = tag_slugs
+            .into_iter()
+            .chain(problem.extra_tags.iter().map(String::as_str))
+            .collect::<Vec<_>>();
+
+        let code_htmls = problem
+            .submissions
+            .iter()
+            .map(|code| {
+                markdown_to_html_with_plugins(
+                    &code.to_markdown().unwrap(),
+                    comrak_options,
+                    comrak_plugins,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let hints_html = if problem.description.hints.is_empty() {
+            String::new()
+        } else {
+            let items = problem
+                .description
+                .hints
+                .iter()
+                .map(|hint| format!("<li>{}</li>", hint))
+                .collect::<String>();
+            format!("<ol>{}</ol>", items)
+        };
+
+        let editorial_html = problem
+            .editorial
+            .as_deref()
+            .map(|editorial| {
+                markdown_to_html_with_plugins(editorial, comrak_options, comrak_plugins)
+            })
+            .unwrap_or_default();
+
+        let fields = vec![
+            problem.description.question_frontend_id.clone(),
+            problem.description.question_title.clone(),
+            problem.description.question_title_slug.clone(),
+            problem.description.difficulty.to_string(),
+            problem.description.content.clone(),
+            tags_str,
+            tag_slugs_str,
+            code_htmls,
+            String::new(),
+            hints_html,
+            editorial_html,
+        ];
+        let tags = note_tags.into_iter().map(str::to_string).collect();
+
+        Self { fields, tags }
+    }
+}
+
 pub fn set_up_comrak_syntect_adapter() -> Result<SyntectAdapter, AnkiError> {
     let theme_file = include_str!("../assets/themes/Dracula.tmTheme");
     let mut theme_reader = Cursor::new(theme_file);
@@ -182,9 +240,33 @@ pub fn set_up_comrak_syntect_adapter() -> Result<SyntectAdapter, AnkiError> {
     Ok(syntect_adapter)
 }
 
-fn set_up_comrak_plugins(syntect_adapter: &SyntectAdapter) -> Plugins<'_> {
+/// Returns the configured model id, replacing the id of the legacy note type, whose fields differ.
+fn model_id(configured_id: i64) -> i64 {
+    if configured_id != LEGACY_MODEL_ID {
+        return configured_id;
+    }
+
+    warn!(
+        "anki.model.id {} belongs to the note type without the Hints and Editorial fields, using {} instead. Set anki.model.id to {} to silence this warning",
+        LEGACY_MODEL_ID, MODEL_ID, MODEL_ID
+    );
+    MODEL_ID
+}
+
+pub(crate) fn set_up_comrak_plugins(syntect_adapter: &SyntectAdapter) -> Plugins<'_> {
     let mut plugins = Plugins::default();
     plugins.render.codefence_syntax_highlighter = Some(syntect_adapter);
 
     plugins
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_id_replaces_legacy_note_type() {
+        assert_eq!(model_id(LEGACY_MODEL_ID), MODEL_ID);
+        assert_eq!(model_id(42), 42);
+    }
+}
@@ -0,0 +1,669 @@
+use crate::anki::{
+    BACK_TEMPLATE, CSS, FIELD_NAMES, FRONT_TEMPLATE, NoteContent, TEMPLATE_NAME,
+    set_up_comrak_plugins,
+};
+use crate::config::AnkiConfig;
+use crate::crawler::leetcode::LeetCodeProblem;
+use crate::error::AnkiError;
+use comrak::Options;
+use comrak::options::Plugins;
+use comrak::plugins::syntect::SyntectAdapter;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use tracing::{debug, info, warn};
+use url::Url;
+
+const ANKI_CONNECT_VERSION: u8 = 6;
+/// Field left untouched when a note is updated, so that personal notes written in Anki survive.
+const PERSONAL_FIELD: &str = "Note";
+
+/// What happened to the note of a pushed problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushOutcome {
+    Added,
+    Updated,
+    Unchanged,
+}
+
+/// The parts of an AnkiConnect `notesInfo` result used to update a note.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NoteInfo {
+    note_id: i64,
+    fields: HashMap<String, NoteField>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NoteField {
+    value: String,
+}
+
+/// Creates and updates notes in a running Anki through the AnkiConnect add-on, instead of writing
+/// an `.apkg` file. Notes are matched by their `ID` field, so pushing a problem again updates it.
+pub struct AnkiConnectClient<'c, 'p> {
+    client: reqwest::Client,
+    endpoint: Url,
+    api_key: Option<String>,
+    deck_name: String,
+    model_name: String,
+    comrak_options: Options<'c>,
+    comrak_plugins: Plugins<'p>,
+}
+
+impl<'c, 'p> AnkiConnectClient<'c, 'p> {
+    pub fn new(anki_config: &AnkiConfig, syntect_adapter: &'p SyntectAdapter) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoint: anki_config.connect.endpoint.clone(),
+            api_key: anki_config.connect.api_key.clone(),
+            deck_name: anki_config.deck.name.clone(),
+            model_name: anki_config.model.name.clone(),
+            comrak_options: Options::default(),
+            comrak_plugins: set_up_comrak_plugins(syntect_adapter),
+        }
+    }
+
+    /// Creates the deck and the LeetCode note type, unless they already exist. Fields missing from
+    /// an existing note type, such as `Hints` and `Editorial` in older ones, are added to it, and
+    /// its card template and styling are replaced when they differ from Lanci's.
+    pub async fn prepare(&self) -> Result<(), AnkiError> {
+        let _: i64 = self
+            .invoke("createDeck", json!({ "deck": self.deck_name }))
+            .await?;
+
+        let model_names: Vec<String> = self.invoke("modelNames", json!({})).await?;
+        if model_names.contains(&self.model_name) {
+            let field_names: Vec<String> = self
+                .invoke("modelFieldNames", json!({ "modelName": self.model_name }))
+                .await?;
+            for (index, field_name) in FIELD_NAMES.iter().enumerate() {
+                if !field_names.iter().any(|name| name == field_name) {
+                    info!(
+                        "Adding field {} to note type {} in Anki",
+                        field_name, self.model_name
+                    );
+                    let _: Value = self
+                        .invoke(
+                            "modelFieldAdd",
+                            json!({
+                                "modelName": self.model_name,
+                                "fieldName": field_name,
+                                "index": index,
+                            }),
+                        )
+                        .await?;
+                }
+            }
+            self.update_model_templates().await?;
+        } else {
+            debug!("Creating note type {} in Anki", self.model_name);
+            let _: Value = self
+                .invoke(
+                    "createModel",
+                    json!({
+                        "modelName": self.model_name,
+                        "inOrderFields": FIELD_NAMES,
+                        "css": CSS,
+                        "isCloze": false,
+                        "cardTemplates": [{
+                            "Name": TEMPLATE_NAME,
+                            "Front": FRONT_TEMPLATE,
+                            "Back": BACK_TEMPLATE,
+                        }],
+                    }),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Brings the card template and the CSS of an existing note type up to date, so that fields
+    /// added in later versions are rendered.
+    async fn update_model_templates(&self) -> Result<(), AnkiError> {
+        let templates: Map<String, Value> = self
+            .invoke("modelTemplates", json!({ "modelName": self.model_name }))
+            .await?;
+        let template = json!({ "Front": FRONT_TEMPLATE, "Back": BACK_TEMPLATE });
+        if templates.get(TEMPLATE_NAME) != Some(&template) {
+            info!(
+                "Updating the card template of note type {} in Anki",
+                self.model_name
+            );
+            let _: Value = self
+                .invoke(
+                    "updateModelTemplates",
+                    json!({
+                        "model": {
+                            "name": self.model_name,
+                            "templates": { TEMPLATE_NAME: template },
+                        }
+                    }),
+                )
+                .await?;
+        }
+
+        let styling: Value = self
+            .invoke("modelStyling", json!({ "modelName": self.model_name }))
+            .await?;
+        if styling["css"] != CSS {
+            info!(
+                "Updating the styling of note type {} in Anki",
+                self.model_name
+            );
+            let _: Value = self
+                .invoke(
+                    "updateModelStyling",
+                    json!({ "model": { "name": self.model_name, "css": CSS } }),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds the note of a problem, or updates the notes that already have its problem ID. Only the
+    /// fields whose content differs are written, and the `Note` field is never overwritten. Cards
+    /// outside the configured deck and its subdecks are moved back into it.
+    pub async fn push_problem(&self, problem: &LeetCodeProblem) -> Result<PushOutcome, AnkiError> {
+        let content = NoteContent::new(problem, &self.comrak_options, &self.comrak_plugins);
+        let mut fields = FIELD_NAMES
+            .iter()
+            .map(|name| name.to_string())
+            .zip(content.fields.into_iter().map(Value::String))
+            .collect::<Map<_, _>>();
+
+        let query = format!(
+            "\"note:{}\" \"ID:{}\"",
+            self.model_name, problem.description.question_frontend_id
+        );
+        let note_ids: Vec<i64> = self.invoke("findNotes", json!({ "query": query })).await?;
+
+        if note_ids.is_empty() {
+            let _: i64 = self
+                .invoke(
+                    "addNote",
+                    json!({
+                        "note": {
+                            "deckName": self.deck_name,
+                            "modelName": self.model_name,
+                            "fields": fields,
+                            "tags": content.tags,
+                        }
+                    }),
+                )
+                .await?;
+
+            return Ok(PushOutcome::Added);
+        }
+
+        if note_ids.len() > 1 {
+            warn!(
+                "Found {} notes for problem {}, updating all of them",
+                note_ids.len(),
+                problem.description.question_frontend_id
+            );
+        }
+        fields.remove(PERSONAL_FIELD);
+        let mut outcome = PushOutcome::Unchanged;
+        let notes: Vec<NoteInfo> = self
+            .invoke("notesInfo", json!({ "notes": note_ids }))
+            .await?;
+        for note in notes {
+            let changed_fields = fields
+                .iter()
+                .filter(|(name, value)| {
+                    note.fields
+                        .get(name.as_str())
+                        .is_none_or(|field| Some(field.value.as_str()) != value.as_str())
+                })
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect::<Map<_, _>>();
+            if changed_fields.is_empty() {
+                continue;
+            }
+
+            debug!(
+                "Updating fields {:?} of note {}",
+                changed_fields.keys().collect::<Vec<_>>(),
+                note.note_id
+            );
+            let _: Value = self
+                .invoke(
+                    "updateNoteFields",
+                    json!({ "note": { "id": note.note_id, "fields": changed_fields } }),
+                )
+                .await?;
+            outcome = PushOutcome::Updated;
+        }
+
+        let stray_cards: Vec<i64> = self
+            .invoke(
+                "findCards",
+                json!({ "query": format!("{} -\"deck:{}\"", query, self.deck_name) }),
+            )
+            .await?;
+        if !stray_cards.is_empty() {
+            debug!(
+                "Moving {} card(s) of problem {} to deck {}",
+                stray_cards.len(),
+                problem.description.question_frontend_id,
+                self.deck_name
+            );
+            let _: Value = self
+                .invoke(
+                    "changeDeck",
+                    json!({ "cards": stray_cards, "deck": self.deck_name }),
+                )
+                .await?;
+            outcome = PushOutcome::Updated;
+        }
+
+        // Tags are only added, tags given in Anki are kept
+        let _: Value = self
+            .invoke(
+                "addTags",
+                json!({ "notes": note_ids, "tags": content.tags.join(" ") }),
+            )
+            .await?;
+
+        Ok(outcome)
+    }
+
+    /// Calls an AnkiConnect action and returns its result.
+    async fn invoke<T: DeserializeOwned>(
+        &self,
+        action: &'static str,
+        params: Value,
+    ) -> Result<T, AnkiError> {
+        #[derive(serde::Deserialize)]
+        struct AnkiConnectResponse {
+            result: Value,
+            error: Option<String>,
+        }
+
+        let mut request = json!({
+            "action": action,
+            "version": ANKI_CONNECT_VERSION,
+            "params": params,
+        });
+        if let Some(api_key) = &self.api_key {
+            request["key"] = json!(api_key);
+        }
+
+        let response: AnkiConnectResponse = self
+            .client
+            .post(self.endpoint.clone())
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(message) = response.error {
+            return Err(AnkiError::AnkiConnectError { action, message });
+        }
+
+        serde_json::from_value(response.result).map_err(|e| AnkiError::AnkiConnectError {
+            action,
+            message: format!("unexpected result: {}", e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anki::set_up_comrak_syntect_adapter;
+    use crate::config::AnkiConnectConfig;
+    use wiremock::matchers::{body_partial_json, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn anki_config(server: &MockServer) -> AnkiConfig {
+        AnkiConfig {
+            connect: AnkiConnectConfig {
+                endpoint: Url::parse(&server.uri()).unwrap(),
+                api_key: Some("secret".to_string()),
+            },
+            ..AnkiConfig::default()
+        }
+    }
+
+    fn problem() -> LeetCodeProblem {
+        LeetCodeProblem {
+            name: "1. Two Sum".to_string(),
+            description: serde_json::from_value(json!({
+                "questionFrontendId": "1",
+                "questionTitle": "Two Sum",
+                "questionTitleSlug": "two-sum",
+                "content": "<p>Find two numbers.</p>",
+                "difficulty": "Easy",
+                "topicTags": [{ "name": "Array", "slug": "array" }],
+                "hints": []
+            }))
+            .unwrap(),
+            editorial: None,
+            submissions: Vec::new(),
+            extra_tags: Vec::new(),
+        }
+    }
+
+    async fn mock_action(server: &MockServer, request: Value, result: Value, calls: u64) {
+        Mock::given(method("POST"))
+            .and(body_partial_json(request))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "result": result, "error": null })),
+            )
+            .expect(calls)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_push_problem_adds_new_note() {
+        let server = MockServer::start().await;
+        mock_action(
+            &server,
+            json!({ "action": "findNotes", "key": "secret", "params": { "query": "\"note:LeetCode\" \"ID:1\"" } }),
+            json!([]),
+            1,
+        )
+        .await;
+        mock_action(
+            &server,
+            json!({
+                "action": "addNote",
+                "params": { "note": {
+                    "deckName": "LeetCode",
+                    "fields": { "ID": "1", "Title": "Two Sum", "Note": "" },
+                    "tags": ["array"]
+                } }
+            }),
+            json!(1700000000000_i64),
+            1,
+        )
+        .await;
+
+        let syntect_adapter = set_up_comrak_syntect_adapter().unwrap();
+        let client = AnkiConnectClient::new(&anki_config(&server), &syntect_adapter);
+
+        assert_eq!(
+            client.push_problem(&problem()).await.unwrap(),
+            PushOutcome::Added
+        );
+    }
+
+    #[tokio::test]
+    async fn test_push_problem_updates_changed_fields_and_moves_cards_to_deck() {
+        let server = MockServer::start().await;
+        mock_action(&server, json!({ "action": "findNotes" }), json!([42]), 1).await;
+        mock_action(
+            &server,
+            json!({ "action": "notesInfo", "params": { "notes": [42] } }),
+            json!([{
+                "noteId": 42,
+                "fields": {
+                    "ID": { "value": "1", "order": 0 },
+                    "Title": { "value": "Two Sum", "order": 1 },
+                    "Description": { "value": "<p>Edited in Anki.</p>", "order": 4 },
+                    "Note": { "value": "My note", "order": 8 }
+                },
+                "cards": [7, 8]
+            }]),
+            1,
+        )
+        .await;
+        mock_action(
+            &server,
+            json!({ "action": "updateNoteFields", "params": { "note": { "id": 42 } } }),
+            Value::Null,
+            1,
+        )
+        .await;
+        mock_action(
+            &server,
+            json!({
+                "action": "findCards",
+                "params": { "query": "\"note:LeetCode\" \"ID:1\" -\"deck:LeetCode\"" }
+            }),
+            json!([8]),
+            1,
+        )
+        .await;
+        mock_action(
+            &server,
+            json!({ "action": "changeDeck", "params": { "cards": [8], "deck": "LeetCode" } }),
+            Value::Null,
+            1,
+        )
+        .await;
+        mock_action(
+            &server,
+            json!({ "action": "addTags", "params": { "notes": [42], "tags": "array" } }),
+            Value::Null,
+            1,
+        )
+        .await;
+        mock_action(&server, json!({ "action": "addNote" }), json!(1), 0).await;
+
+        let syntect_adapter = set_up_comrak_syntect_adapter().unwrap();
+        let client = AnkiConnectClient::new(&anki_config(&server), &syntect_adapter);
+
+        assert_eq!(
+            client.push_problem(&problem()).await.unwrap(),
+            PushOutcome::Updated
+        );
+        let requests = server.received_requests().await.unwrap();
+        let update: Value = requests
+            .iter()
+            .map(|request| request.body_json::<Value>().unwrap())
+            .find(|body| body["action"] == "updateNoteFields")
+            .unwrap();
+        let updated_fields = update["params"]["note"]["fields"].as_object().unwrap();
+        assert!(updated_fields.contains_key("Description"));
+        assert!(updated_fields.contains_key("Difficulty"));
+        assert!(!updated_fields.contains_key("ID"));
+        assert!(!updated_fields.contains_key("Title"));
+        assert!(!updated_fields.contains_key("Note"));
+    }
+
+    #[tokio::test]
+    async fn test_push_problem_leaves_unchanged_note_alone() {
+        let server = MockServer::start().await;
+        let syntect_adapter = set_up_comrak_syntect_adapter().unwrap();
+        let client = AnkiConnectClient::new(&anki_config(&server), &syntect_adapter);
+        let content = NoteContent::new(&problem(), &client.comrak_options, &client.comrak_plugins);
+        let fields = FIELD_NAMES
+            .iter()
+            .zip(content.fields)
+            .map(|(name, value)| (name.to_string(), json!({ "value": value })))
+            .collect::<Map<_, _>>();
+
+        mock_action(&server, json!({ "action": "findNotes" }), json!([42]), 1).await;
+        mock_action(
+            &server,
+            json!({ "action": "notesInfo" }),
+            json!([{ "noteId": 42, "fields": fields, "cards": [7] }]),
+            1,
+        )
+        .await;
+        mock_action(&server, json!({ "action": "findCards" }), json!([]), 1).await;
+        mock_action(&server, json!({ "action": "addTags" }), Value::Null, 1).await;
+        mock_action(
+            &server,
+            json!({ "action": "updateNoteFields" }),
+            Value::Null,
+            0,
+        )
+        .await;
+        mock_action(&server, json!({ "action": "changeDeck" }), Value::Null, 0).await;
+
+        assert_eq!(
+            client.push_problem(&problem()).await.unwrap(),
+            PushOutcome::Unchanged
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prepare_adds_missing_fields_to_existing_note_type() {
+        let server = MockServer::start().await;
+        mock_action(&server, json!({ "action": "createDeck" }), json!(1), 1).await;
+        mock_action(
+            &server,
+            json!({ "action": "modelNames" }),
+            json!(["Basic", "LeetCode"]),
+            1,
+        )
+        .await;
+        let old_field_names = FIELD_NAMES
+            .iter()
+            .filter(|name| !["Hints", "Editorial"].contains(name))
+            .collect::<Vec<_>>();
+        mock_action(
+            &server,
+            json!({ "action": "modelFieldNames", "params": { "modelName": "LeetCode" } }),
+            json!(old_field_names),
+            1,
+        )
+        .await;
+        mock_action(
+            &server,
+            json!({ "action": "modelFieldAdd", "params": { "fieldName": "Hints" } }),
+            Value::Null,
+            1,
+        )
+        .await;
+        mock_action(
+            &server,
+            json!({ "action": "modelFieldAdd", "params": { "fieldName": "Editorial" } }),
+            Value::Null,
+            1,
+        )
+        .await;
+        mock_action(
+            &server,
+            json!({ "action": "modelTemplates", "params": { "modelName": "LeetCode" } }),
+            json!({ "LeetCode": { "Front": "{{Title}}", "Back": "{{Description}}" } }),
+            1,
+        )
+        .await;
+        mock_action(
+            &server,
+            json!({
+                "action": "updateModelTemplates",
+                "params": { "model": {
+                    "name": "LeetCode",
+                    "templates": { "LeetCode": { "Front": FRONT_TEMPLATE, "Back": BACK_TEMPLATE } }
+                } }
+            }),
+            Value::Null,
+            1,
+        )
+        .await;
+        mock_action(
+            &server,
+            json!({ "action": "modelStyling", "params": { "modelName": "LeetCode" } }),
+            json!({ "css": ".card { color: black; }" }),
+            1,
+        )
+        .await;
+        mock_action(
+            &server,
+            json!({
+                "action": "updateModelStyling",
+                "params": { "model": { "name": "LeetCode", "css": CSS } }
+            }),
+            Value::Null,
+            1,
+        )
+        .await;
+        mock_action(&server, json!({ "action": "createModel" }), Value::Null, 0).await;
+
+        let syntect_adapter = set_up_comrak_syntect_adapter().unwrap();
+        let client = AnkiConnectClient::new(&anki_config(&server), &syntect_adapter);
+
+        client.prepare().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_prepare_leaves_up_to_date_note_type_alone() {
+        let server = MockServer::start().await;
+        mock_action(&server, json!({ "action": "createDeck" }), json!(1), 1).await;
+        mock_action(
+            &server,
+            json!({ "action": "modelNames" }),
+            json!(["LeetCode"]),
+            1,
+        )
+        .await;
+        mock_action(
+            &server,
+            json!({ "action": "modelFieldNames" }),
+            json!(FIELD_NAMES),
+            1,
+        )
+        .await;
+        mock_action(
+            &server,
+            json!({ "action": "modelTemplates" }),
+            json!({ "LeetCode": { "Front": FRONT_TEMPLATE, "Back": BACK_TEMPLATE } }),
+            1,
+        )
+        .await;
+        mock_action(
+            &server,
+            json!({ "action": "modelStyling" }),
+            json!({ "css": CSS }),
+            1,
+        )
+        .await;
+        for action in [
+            "modelFieldAdd",
+            "updateModelTemplates",
+            "updateModelStyling",
+        ] {
+            mock_action(&server, json!({ "action": action }), Value::Null, 0).await;
+        }
+
+        let syntect_adapter = set_up_comrak_syntect_adapter().unwrap();
+        let client = AnkiConnectClient::new(&anki_config(&server), &syntect_adapter);
+
+        client.prepare().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_prepare_creates_missing_note_type_and_reports_errors() {
+        let server = MockServer::start().await;
+        mock_action(&server, json!({ "action": "createDeck" }), json!(1), 1).await;
+        mock_action(
+            &server,
+            json!({ "action": "modelNames" }),
+            json!(["Basic"]),
+            1,
+        )
+        .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "action": "createModel" })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "result": null, "error": "Model name already exists" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let syntect_adapter = set_up_comrak_syntect_adapter().unwrap();
+        let client = AnkiConnectClient::new(&anki_config(&server), &syntect_adapter);
+
+        assert!(matches!(
+            client.prepare().await,
+            Err(AnkiError::AnkiConnectError {
+                action: "createModel",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,255 @@
+use crate::config::LeetCodeCookies;
+use crate::crawler::leetcode::LeetCodeSite;
+use crate::error::{BrowserCookieError, ConfigParseError};
+use rusqlite::{Connection, OpenFlags, params};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const FIREFOX_COOKIE_DB: &str = "cookies.sqlite";
+const CHROMIUM_COOKIE_DBS: [&str; 2] = ["Network/Cookies", "Cookies"];
+/// Seconds between 1601-01-01, the epoch of Chromium timestamps, and the Unix epoch.
+const CHROMIUM_EPOCH_OFFSET_SECS: i64 = 11_644_473_600;
+
+/// The browser family a cookie database belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BrowserKind {
+    Firefox,
+    Chromium,
+}
+
+/// Reads the LeetCode cookies from a local Firefox or Chromium profile.
+/// `path` can be the profile directory or the cookie database itself. When `browser` is `None`,
+/// it is guessed from the files found at `path`.
+pub fn import_cookies(
+    path: &Path,
+    browser: Option<BrowserKind>,
+    site: LeetCodeSite,
+) -> Result<LeetCodeCookies, BrowserCookieError> {
+    let (browser, db_path) = locate_cookie_db(path, browser)?;
+
+    // The browser keeps its database locked (and recent writes in the WAL file) while it runs,
+    // so read from a private copy instead.
+    let temp_dir = tempfile::tempdir()?;
+    let copy_path = temp_dir.path().join("cookies.sqlite");
+    std::fs::copy(&db_path, &copy_path)?;
+    let wal_path = PathBuf::from(format!("{}-wal", db_path.display()));
+    if wal_path.exists() {
+        std::fs::copy(&wal_path, temp_dir.path().join("cookies.sqlite-wal"))?;
+    }
+
+    let connection = Connection::open_with_flags(&copy_path, OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+    let subdomains = format!(".{}", site.cookie_domain());
+    let hosts = [site.cookie_domain(), subdomains.as_str()];
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let pairs = match browser {
+        BrowserKind::Firefox => read_firefox_cookies(&connection, hosts, now)?,
+        BrowserKind::Chromium => read_chromium_cookies(&connection, hosts, now)?,
+    };
+
+    if pairs.is_empty() {
+        return Err(BrowserCookieError::NoCookies(db_path.display().to_string()));
+    }
+
+    let raw = pairs
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    LeetCodeCookies::from_str(&raw).map_err(|e| match e {
+        ConfigParseError::CookieParseError(field) => BrowserCookieError::MissingCookie(field),
+        other => BrowserCookieError::Other(other.to_string()),
+    })
+}
+
+/// Finds the cookie database for the given profile path.
+fn locate_cookie_db(
+    path: &Path,
+    browser: Option<BrowserKind>,
+) -> Result<(BrowserKind, PathBuf), BrowserCookieError> {
+    if path.is_file() {
+        let is_firefox = path
+            .file_name()
+            .is_some_and(|name| name == FIREFOX_COOKIE_DB);
+        let browser = browser.unwrap_or(if is_firefox {
+            BrowserKind::Firefox
+        } else {
+            BrowserKind::Chromium
+        });
+
+        return Ok((browser, path.to_path_buf()));
+    }
+
+    let firefox_db = path.join(FIREFOX_COOKIE_DB);
+    if browser != Some(BrowserKind::Chromium) && firefox_db.is_file() {
+        return Ok((BrowserKind::Firefox, firefox_db));
+    }
+
+    if browser != Some(BrowserKind::Firefox)
+        && let Some(chromium_db) = CHROMIUM_COOKIE_DBS
+            .iter()
+            .map(|db| path.join(db))
+            .find(|db| db.is_file())
+    {
+        return Ok((BrowserKind::Chromium, chromium_db));
+    }
+
+    Err(BrowserCookieError::DatabaseNotFound(
+        path.display().to_string(),
+    ))
+}
+
+/// Reads the unexpired cookies of the given hosts. `now` is a Unix timestamp in seconds.
+fn read_firefox_cookies(
+    connection: &Connection,
+    hosts: [&str; 2],
+    now: i64,
+) -> Result<Vec<(String, String)>, BrowserCookieError> {
+    // `expiry` is in seconds, or in milliseconds in recent Firefox versions
+    let mut statement = connection.prepare(
+        "SELECT name, value FROM moz_cookies WHERE host IN (?1, ?2)
+         AND (CASE WHEN expiry > 100000000000 THEN expiry / 1000 ELSE expiry END) > ?3
+         ORDER BY lastAccessed",
+    )?;
+    let rows = statement.query_map(params![hosts[0], hosts[1], now], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+
+    Ok(dedup_by_name(rows.collect::<Result<Vec<_>, _>>()?))
+}
+
+/// Reads the unexpired cookies of the given hosts. `now` is a Unix timestamp in seconds.
+fn read_chromium_cookies(
+    connection: &Connection,
+    hosts: [&str; 2],
+    now: i64,
+) -> Result<Vec<(String, String)>, BrowserCookieError> {
+    // `expires_utc` is in microseconds since 1601, and 0 for session cookies
+    let now_utc = (now + CHROMIUM_EPOCH_OFFSET_SECS) * 1_000_000;
+    let mut statement = connection.prepare(
+        "SELECT name, value, length(encrypted_value) FROM cookies WHERE host_key IN (?1, ?2)
+         AND (expires_utc = 0 OR expires_utc > ?3)
+         ORDER BY last_access_utc",
+    )?;
+    let rows = statement.query_map(params![hosts[0], hosts[1], now_utc], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<i64>>(2)?.unwrap_or(0),
+        ))
+    })?;
+
+    let mut pairs = Vec::new();
+    for row in rows {
+        let (name, value, encrypted_len) = row?;
+        if value.is_empty() && encrypted_len > 0 {
+            return Err(BrowserCookieError::EncryptedCookies);
+        }
+        pairs.push((name, value));
+    }
+
+    Ok(dedup_by_name(pairs))
+}
+
+/// Keeps the last occurrence of every cookie name, preserving the order of first appearance.
+fn dedup_by_name(pairs: Vec<(String, String)>) -> Vec<(String, String)> {
+    let mut deduped: Vec<(String, String)> = Vec::with_capacity(pairs.len());
+
+    for (name, value) in pairs {
+        match deduped.iter_mut().find(|(existing, _)| *existing == name) {
+            Some(existing) => existing.1 = value,
+            None => deduped.push((name, value)),
+        }
+    }
+
+    deduped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_cookies_from_firefox_profile() {
+        let profile = tempfile::tempdir().unwrap();
+        let connection = Connection::open(profile.path().join(FIREFOX_COOKIE_DB)).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE moz_cookies (name TEXT, value TEXT, host TEXT, expiry INTEGER, lastAccessed INTEGER);
+                 INSERT INTO moz_cookies VALUES ('csrftoken', 'old', '.leetcode.com', 4102444800, 1);
+                 INSERT INTO moz_cookies VALUES ('LEETCODE_SESSION', 'xyz789', '.leetcode.com', 4102444800000, 2);
+                 INSERT INTO moz_cookies VALUES ('csrftoken', 'abc123', 'leetcode.com', 4102444800, 3);
+                 INSERT INTO moz_cookies VALUES ('LEETCODE_SESSION', 'expired', 'leetcode.com', 1600000000, 4);
+                 INSERT INTO moz_cookies VALUES ('cf_clearance', 'expired', 'leetcode.com', 1600000000000, 5);
+                 INSERT INTO moz_cookies VALUES ('other', 'ignored', 'example.com', 4102444800, 6);",
+            )
+            .unwrap();
+        drop(connection);
+
+        let cookies = import_cookies(profile.path(), None, LeetCodeSite::Com).unwrap();
+
+        assert_eq!(cookies.raw, "csrftoken=abc123; LEETCODE_SESSION=xyz789");
+        assert_eq!(cookies.csrf_token, "abc123");
+        assert_eq!(cookies.leet_code_token, "xyz789");
+    }
+
+    #[test]
+    fn test_import_cookies_from_chromium_profile() {
+        let profile = tempfile::tempdir().unwrap();
+        let connection = Connection::open(profile.path().join("Cookies")).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE cookies (host_key TEXT, name TEXT, value TEXT, encrypted_value BLOB, expires_utc INTEGER, last_access_utc INTEGER);
+                 INSERT INTO cookies VALUES ('.leetcode.com', 'csrftoken', 'abc123', x'', 0, 1);
+                 INSERT INTO cookies VALUES ('.leetcode.com', 'LEETCODE_SESSION', 'xyz789', x'', 15747004800000000, 2);
+                 INSERT INTO cookies VALUES ('.leetcode.com', 'LEETCODE_SESSION', 'expired', x'', 13244473600000000, 3);",
+            )
+            .unwrap();
+        drop(connection);
+
+        let cookies = import_cookies(
+            profile.path(),
+            Some(BrowserKind::Chromium),
+            LeetCodeSite::Com,
+        )
+        .unwrap();
+
+        assert_eq!(cookies.csrf_token, "abc123");
+        assert_eq!(cookies.leet_code_token, "xyz789");
+    }
+
+    #[test]
+    fn test_import_encrypted_chromium_cookies_fails() {
+        let profile = tempfile::tempdir().unwrap();
+        let db_path = profile.path().join("Cookies");
+        let connection = Connection::open(&db_path).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE cookies (host_key TEXT, name TEXT, value TEXT, encrypted_value BLOB, expires_utc INTEGER, last_access_utc INTEGER);
+                 INSERT INTO cookies VALUES ('.leetcode.com', 'csrftoken', '', x'763130', 0, 1);",
+            )
+            .unwrap();
+        drop(connection);
+
+        assert!(matches!(
+            import_cookies(&db_path, None, LeetCodeSite::Com),
+            Err(BrowserCookieError::EncryptedCookies)
+        ));
+    }
+
+    #[test]
+    fn test_import_cookies_without_database_fails() {
+        let profile = tempfile::tempdir().unwrap();
+
+        assert!(matches!(
+            import_cookies(profile.path(), None, LeetCodeSite::Com),
+            Err(BrowserCookieError::DatabaseNotFound(_))
+        ));
+    }
+}
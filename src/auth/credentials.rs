@@ -0,0 +1,122 @@
+use crate::error::ConfigParseError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use tracing::warn;
+
+/// Environment variable that takes precedence over every other cookie source.
+pub const COOKIE_ENV_VAR: &str = "LANCI_COOKIE";
+
+/// Secrets kept outside of `config.json`, so the config can be shared or committed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Credentials {
+    #[serde(default)]
+    pub cookie: Option<String>,
+}
+
+impl Credentials {
+    pub fn load_from_file(path: &Path) -> Result<Self, ConfigParseError> {
+        warn_if_world_readable(path);
+        let content = fs::read_to_string(path)?;
+        let credentials = serde_json::from_str(&content)?;
+
+        Ok(credentials)
+    }
+
+    /// Writes the credentials file, readable and writable by the current user only.
+    pub fn save_to_file(&self, path: &Path) -> Result<(), ConfigParseError> {
+        let content = serde_json::to_string_pretty(self)?;
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options
+            .open(path)
+            .map_err(ConfigParseError::WriteFileError)?;
+        // `mode` only applies to new files, so also tighten an existing one
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))
+                .map_err(ConfigParseError::WriteFileError)?;
+        }
+        file.write_all(content.as_bytes())
+            .map_err(ConfigParseError::WriteFileError)?;
+
+        Ok(())
+    }
+}
+
+/// Reads a raw `Cookie` header from a file.
+pub fn read_cookie_file(path: &Path) -> Result<String, ConfigParseError> {
+    warn_if_world_readable(path);
+    let content = fs::read_to_string(path)?;
+
+    Ok(content.trim().to_string())
+}
+
+/// Logs a warning when a file holding secrets can be read by any user on the system.
+pub(crate) fn warn_if_world_readable(path: &Path) {
+    if is_world_readable(path) {
+        warn!(
+            "{} is readable by all users, restrict it with `chmod 600 {}`",
+            path.display(),
+            path.display()
+        );
+    }
+}
+
+#[cfg(unix)]
+fn is_world_readable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o004 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_world_readable(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load_credentials() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("credentials.json");
+        let credentials = Credentials {
+            cookie: Some("csrftoken=abc123; LEETCODE_SESSION=xyz789".to_string()),
+        };
+
+        credentials.save_to_file(&path).unwrap();
+        let loaded = Credentials::load_from_file(&path).unwrap();
+
+        assert_eq!(loaded.cookie, credentials.cookie);
+        assert!(!is_world_readable(&path));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_detect_world_readable_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cookie.txt");
+        fs::write(&path, " csrftoken=abc123; LEETCODE_SESSION=xyz789\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        assert!(is_world_readable(&path));
+        assert_eq!(
+            read_cookie_file(&path).unwrap(),
+            "csrftoken=abc123; LEETCODE_SESSION=xyz789"
+        );
+    }
+}
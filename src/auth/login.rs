@@ -0,0 +1,61 @@
+use crate::config::{LeetCodeCookies, NetworkConfig, WebDriverConfig};
+use crate::crawler::leetcode::LeetCodeSite;
+use crate::crawler::web_driver::WebDriverSession;
+use crate::error::CrawlerError;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use tracing::info;
+
+const LOGIN_PATH: &str = "/accounts/login/";
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Opens a visible browser at the LeetCode login page and waits until the user has signed in.
+/// Returns the session cookies read back from the browser.
+pub async fn login_with_web_driver(
+    web_driver_config: &WebDriverConfig,
+    network: &NetworkConfig,
+    site: LeetCodeSite,
+    timeout: Duration,
+) -> Result<LeetCodeCookies, CrawlerError> {
+    // The user has to interact with the page, so never start the browser headless.
+    let web_driver = WebDriverSession::connect(web_driver_config, network, false).await?;
+
+    let result = wait_for_login(&web_driver, site, timeout).await;
+    web_driver.close().await?;
+
+    result
+}
+
+async fn wait_for_login(
+    web_driver: &fantoccini::Client,
+    site: LeetCodeSite,
+    timeout: Duration,
+) -> Result<LeetCodeCookies, CrawlerError> {
+    web_driver
+        .goto(&format!("{}{}", site.host(), LOGIN_PATH))
+        .await?;
+    info!(
+        "Sign in to LeetCode in the opened browser window, waiting up to {} seconds...",
+        timeout.as_secs()
+    );
+
+    let started = Instant::now();
+    while started.elapsed() < timeout {
+        let cookies = web_driver.get_all_cookies().await?;
+        let raw = cookies
+            .iter()
+            .filter(|cookie| !cookie.value().is_empty())
+            .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        if let Ok(cookies) = LeetCodeCookies::from_str(&raw) {
+            return Ok(cookies);
+        }
+
+        sleep(POLL_INTERVAL).await;
+    }
+
+    Err(CrawlerError::LoginTimedOut(timeout.as_secs()))
+}
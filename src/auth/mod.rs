@@ -0,0 +1,4 @@
+pub mod browser;
+pub mod credentials;
+pub mod login;
+pub mod session;
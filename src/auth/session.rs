@@ -0,0 +1,69 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Sessions expiring sooner than this are reported as about to expire.
+pub const EXPIRY_WARNING_THRESHOLD: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+/// Decodes the expiry (Unix timestamp in seconds) of a `LEETCODE_SESSION` token.
+/// The token is a JWT; its signature is not verified, only the payload is read.
+pub fn session_expiry(leet_code_token: &str) -> Option<u64> {
+    let payload = leet_code_token.trim_matches('"').split('.').nth(1)?;
+    let payload = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&payload).ok()?;
+
+    // LeetCode stores the expiry in `expired_time_`, standard JWTs use `exp`
+    ["exp", "expired_time_"]
+        .iter()
+        .find_map(|claim| claims.get(claim)?.as_u64())
+}
+
+/// Returns how long the session remains valid, or `None` if it has already expired.
+pub fn remaining_validity(expiry: u64) -> Option<Duration> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    expiry
+        .checked_sub(now)
+        .filter(|remaining| *remaining > 0)
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(claims: &str) -> String {
+        format!(
+            "{}.{}.signature",
+            URL_SAFE_NO_PAD.encode(r#"{"typ":"JWT","alg":"HS256"}"#),
+            URL_SAFE_NO_PAD.encode(claims)
+        )
+    }
+
+    #[test]
+    fn test_session_expiry_from_leetcode_claim() {
+        let token = token(r#"{"username":"someone","expired_time_":1700000000}"#);
+        assert_eq!(session_expiry(&token), Some(1700000000));
+    }
+
+    #[test]
+    fn test_session_expiry_from_standard_claim() {
+        let token = token(r#"{"exp":1700000000}"#);
+        assert_eq!(session_expiry(&token), Some(1700000000));
+    }
+
+    #[test]
+    fn test_session_expiry_of_invalid_token() {
+        assert_eq!(session_expiry("not-a-jwt"), None);
+        assert_eq!(session_expiry(&token(r#"{"username":"someone"}"#)), None);
+    }
+
+    #[test]
+    fn test_remaining_validity() {
+        assert_eq!(remaining_validity(0), None);
+        assert!(remaining_validity(u64::MAX).is_some());
+    }
+}
@@ -0,0 +1,423 @@
+use crate::crawler::leetcode::problemset::{ProblemsetQuestion, QuestionListFilter};
+use crate::crawler::leetcode::{LeetCodeCrawler, ProblemQuery};
+use crate::error::CatalogError;
+use crate::metrics;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::{debug, info};
+
+/// A local copy of the LeetCode problemset metadata, used to resolve problems without hitting the API.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Catalog {
+    pub questions: Vec<ProblemsetQuestion>,
+}
+
+impl Catalog {
+    pub fn new(questions: Vec<ProblemsetQuestion>) -> Self {
+        Self { questions }
+    }
+
+    pub async fn load_from_file(path: impl AsRef<Path>) -> Result<Self, CatalogError> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let catalog = serde_json::from_str(&content)?;
+
+        Ok(catalog)
+    }
+
+    pub async fn save_to_file(&self, path: impl AsRef<Path>) -> Result<(), CatalogError> {
+        let content = serde_json::to_string(self)?;
+        tokio::fs::write(path, content).await?;
+
+        Ok(())
+    }
+
+    /// Downloads the full problemset metadata and saves it as the local problem catalog.
+    pub async fn download(
+        crawler: &LeetCodeCrawler,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, CatalogError> {
+        let questions = crawler
+            .fetch_problemset_questions(&QuestionListFilter::default())
+            .await?;
+        let catalog = Self::new(questions);
+        catalog.save_to_file(path).await?;
+
+        Ok(catalog)
+    }
+
+    pub fn find_by_frontend_id(&self, frontend_id: &str) -> Option<&ProblemsetQuestion> {
+        self.questions
+            .iter()
+            .find(|question| question.frontend_question_id == frontend_id)
+    }
+
+    pub fn find_by_slug(&self, slug: &str) -> Option<&ProblemsetQuestion> {
+        self.questions
+            .iter()
+            .find(|question| question.title_slug == slug)
+    }
+
+    /// Returns the questions whose title contains every word of the query, ignoring case and punctuation.
+    /// An exact title match is returned alone; other matches are ordered from the shortest title.
+    pub fn search_title(&self, query: &str) -> Vec<&ProblemsetQuestion> {
+        let query = normalize_title(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        if let Some(question) = self
+            .questions
+            .iter()
+            .find(|question| normalize_title(&question.title) == query)
+        {
+            return vec![question];
+        }
+
+        let words = query.split(' ').collect::<Vec<_>>();
+        let mut matches = self
+            .questions
+            .iter()
+            .filter(|question| {
+                let title = normalize_title(&question.title);
+                words.iter().all(|word| title.contains(word))
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|question| question.title.len());
+
+        matches
+    }
+
+    /// Searches the catalog offline. Questions must satisfy the filter and, when a query is given,
+    /// match it by frontend id, slug or title words.
+    pub fn search(
+        &self,
+        query: Option<&str>,
+        filter: &QuestionListFilter,
+    ) -> Vec<&ProblemsetQuestion> {
+        let candidates = match query.map(str::trim).filter(|query| !query.is_empty()) {
+            None => self.questions.iter().collect(),
+            Some(query) => match self
+                .find_by_frontend_id(query)
+                .or_else(|| self.find_by_slug(query))
+            {
+                Some(question) => vec![question],
+                None => self.search_title(query),
+            },
+        };
+
+        candidates
+            .into_iter()
+            .filter(|question| filter.matches(question))
+            .collect()
+    }
+
+    /// Resolves a title query to exactly one question, failing when nothing or several questions match.
+    pub fn resolve_title(&self, query: &str) -> Result<&ProblemsetQuestion, CatalogError> {
+        const MAX_CANDIDATES: usize = 5;
+
+        match self.search_title(query).as_slice() {
+            [] => Err(CatalogError::ProblemNotFound(query.to_string())),
+            [question] => Ok(question),
+            candidates => Err(CatalogError::AmbiguousProblem {
+                query: query.to_string(),
+                candidates: candidates
+                    .iter()
+                    .take(MAX_CANDIDATES)
+                    .map(|question| {
+                        format!("{}. {}", question.frontend_question_id, question.title)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            }),
+        }
+    }
+}
+
+/// Resolves problem queries to problem slugs. Frontend ids and titles are looked up in the local
+/// problem catalog, which is loaded on first use and downloaded when it does not exist yet. When a
+/// problem is missing from a catalog loaded from disk, the catalog is downloaded again once, since
+/// it may predate the problem.
+pub struct ProblemResolver<'a> {
+    crawler: &'a LeetCodeCrawler,
+    catalog_path: &'a Path,
+    catalog: Option<Catalog>,
+    /// Whether the catalog was downloaded during this run.
+    up_to_date: bool,
+}
+
+impl<'a> ProblemResolver<'a> {
+    pub fn new(crawler: &'a LeetCodeCrawler, catalog_path: &'a Path) -> Self {
+        Self {
+            crawler,
+            catalog_path,
+            catalog: None,
+            up_to_date: false,
+        }
+    }
+
+    pub async fn resolve(&mut self, query: &ProblemQuery) -> Result<String, CatalogError> {
+        match query {
+            ProblemQuery::Slug(slug) => Ok(slug.clone()),
+            ProblemQuery::SubmissionId(submission_id) => Ok(self
+                .crawler
+                .fetch_submission_question_slug(*submission_id)
+                .await?),
+            ProblemQuery::SlugOrTitle(text) => {
+                if self.crawler.problem_exists(text).await? {
+                    return Ok(text.clone());
+                }
+
+                debug!("No problem has the slug {}, searching the titles", text);
+                self.resolve_in_catalog(text, Catalog::resolve_title).await
+            }
+            ProblemQuery::FrontendId(frontend_id) => {
+                self.resolve_in_catalog(frontend_id, |catalog, frontend_id| {
+                    catalog
+                        .find_by_frontend_id(frontend_id)
+                        .ok_or_else(|| CatalogError::ProblemNotFound(frontend_id.to_string()))
+                })
+                .await
+            }
+            ProblemQuery::Title(title) => {
+                self.resolve_in_catalog(title, Catalog::resolve_title).await
+            }
+        }
+    }
+
+    async fn resolve_in_catalog(
+        &mut self,
+        query: &str,
+        lookup: impl for<'c> Fn(&'c Catalog, &str) -> Result<&'c ProblemsetQuestion, CatalogError>,
+    ) -> Result<String, CatalogError> {
+        let resolve = |catalog: &Catalog| {
+            lookup(catalog, query).map(|question| {
+                info!(
+                    "Resolved problem {}. {}",
+                    question.frontend_question_id, question.title
+                );
+                question.title_slug.clone()
+            })
+        };
+
+        match resolve(self.catalog().await?) {
+            Err(CatalogError::ProblemNotFound(_)) if !self.up_to_date => {
+                info!("No problem matches {} in the catalog, updating it", query);
+                self.catalog = Some(Catalog::download(self.crawler, self.catalog_path).await?);
+                self.up_to_date = true;
+
+                resolve(self.catalog().await?)
+            }
+            result => result,
+        }
+    }
+
+    /// Returns the catalog, loading it from disk or downloading it first.
+    async fn catalog(&mut self) -> Result<&Catalog, CatalogError> {
+        if self.catalog.is_none() {
+            let catalog = if tokio::fs::try_exists(self.catalog_path).await? {
+                metrics::count(metrics::CACHE_HITS);
+                Catalog::load_from_file(self.catalog_path).await?
+            } else {
+                info!(
+                    "Problem catalog not found, downloading it to {}",
+                    self.catalog_path.display()
+                );
+                self.up_to_date = true;
+                Catalog::download(self.crawler, self.catalog_path).await?
+            };
+            self.catalog = Some(catalog);
+        }
+
+        Ok(self.catalog.as_ref().expect("catalog was just loaded"))
+    }
+}
+
+/// Lowercases a title and collapses everything that is not alphanumeric into single spaces.
+fn normalize_title(title: &str) -> String {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crawler::leetcode::question::QuestionDifficulty;
+    use crate::crawler::leetcode::test_support::{mock_graphql, received_operations, test_crawler};
+    use serde_json::json;
+    use wiremock::MockServer;
+
+    fn question(frontend_id: &str, title: &str, slug: &str) -> ProblemsetQuestion {
+        ProblemsetQuestion {
+            frontend_question_id: frontend_id.to_string(),
+            title: title.to_string(),
+            title_slug: slug.to_string(),
+            difficulty: QuestionDifficulty::Easy,
+            is_paid_only: false,
+            ac_rate: 50.0,
+            status: None,
+            topic_tags: Vec::new(),
+        }
+    }
+
+    fn catalog() -> Catalog {
+        Catalog::new(vec![
+            question("1", "Two Sum", "two-sum"),
+            question(
+                "167",
+                "Two Sum II - Input Array Is Sorted",
+                "two-sum-ii-input-array-is-sorted",
+            ),
+            question("15", "3Sum", "3sum"),
+            question("50", "Pow(x, n)", "powx-n"),
+        ])
+    }
+
+    #[test]
+    fn test_find_by_frontend_id_and_slug() {
+        let catalog = catalog();
+
+        assert_eq!(
+            catalog.find_by_frontend_id("15").unwrap().title_slug,
+            "3sum"
+        );
+        assert_eq!(
+            catalog.find_by_slug("powx-n").unwrap().frontend_question_id,
+            "50"
+        );
+        assert!(catalog.find_by_frontend_id("9999").is_none());
+    }
+
+    #[test]
+    fn test_search_with_filter() {
+        let mut catalog = catalog();
+        catalog.questions[2].difficulty = QuestionDifficulty::Medium;
+
+        let filter = QuestionListFilter {
+            difficulty: Some(QuestionDifficulty::Medium),
+            ..Default::default()
+        };
+        let results = catalog.search(None, &filter);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title_slug, "3sum");
+
+        assert_eq!(catalog.search(Some("sum"), &filter).len(), 1);
+        assert!(catalog.search(Some("1"), &filter).is_empty());
+        assert_eq!(
+            catalog.search(Some("1"), &QuestionListFilter::default())[0].title_slug,
+            "two-sum"
+        );
+    }
+
+    #[test]
+    fn test_resolve_title_prefers_exact_match() {
+        let catalog = catalog();
+
+        assert_eq!(
+            catalog.resolve_title("two sum").unwrap().title_slug,
+            "two-sum"
+        );
+        assert_eq!(
+            catalog.resolve_title("POW(X, N)").unwrap().title_slug,
+            "powx-n"
+        );
+        assert_eq!(
+            catalog.resolve_title("sum sorted").unwrap().title_slug,
+            "two-sum-ii-input-array-is-sorted"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_slug_like_text_as_slug_then_title() {
+        let server = MockServer::start().await;
+        mock_graphql(
+            &server,
+            "problemExists",
+            json!({ "titleSlug": "subsets" }),
+            json!({ "data": { "question": { "titleSlug": "subsets" } } }),
+        )
+        .await;
+        mock_graphql(
+            &server,
+            "problemExists",
+            json!({ "titleSlug": "pow" }),
+            json!({ "data": { "question": null } }),
+        )
+        .await;
+        let crawler = test_crawler(&server, false);
+        let dir = tempfile::tempdir().unwrap();
+        let catalog_path = dir.path().join("catalog.json");
+        catalog().save_to_file(&catalog_path).await.unwrap();
+        let mut resolver = ProblemResolver::new(&crawler, &catalog_path);
+
+        let query = |text: &str| ProblemQuery::SlugOrTitle(text.to_string());
+        assert_eq!(
+            resolver.resolve(&query("subsets")).await.unwrap(),
+            "subsets"
+        );
+        assert_eq!(resolver.resolve(&query("pow")).await.unwrap(), "powx-n");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_refreshes_stale_catalog_once() {
+        let server = MockServer::start().await;
+        mock_graphql(
+            &server,
+            "problemsetQuestionList",
+            json!({}),
+            json!({ "data": { "problemsetQuestionList": { "total": 1, "questions": [{
+                "frontendQuestionId": "3000",
+                "title": "Maximum Area of Longest Diagonal Rectangle",
+                "titleSlug": "maximum-area-of-longest-diagonal-rectangle",
+                "difficulty": "Easy",
+                "isPaidOnly": false,
+                "acRate": 36.2,
+                "status": null,
+                "topicTags": []
+            }] } } }),
+        )
+        .await;
+        let crawler = test_crawler(&server, false);
+        let dir = tempfile::tempdir().unwrap();
+        let catalog_path = dir.path().join("catalog.json");
+        catalog().save_to_file(&catalog_path).await.unwrap();
+        let mut resolver = ProblemResolver::new(&crawler, &catalog_path);
+
+        assert_eq!(
+            resolver
+                .resolve(&ProblemQuery::FrontendId("3000".to_string()))
+                .await
+                .unwrap(),
+            "maximum-area-of-longest-diagonal-rectangle"
+        );
+        assert!(matches!(
+            resolver
+                .resolve(&ProblemQuery::FrontendId("9999".to_string()))
+                .await,
+            Err(CatalogError::ProblemNotFound(_))
+        ));
+        assert_eq!(
+            received_operations(&server).await,
+            ["problemsetQuestionList"]
+        );
+        let saved = Catalog::load_from_file(&catalog_path).await.unwrap();
+        assert!(saved.find_by_frontend_id("3000").is_some());
+    }
+
+    #[test]
+    fn test_resolve_title_reports_ambiguous_and_missing_matches() {
+        let catalog = catalog();
+
+        assert!(matches!(
+            catalog.resolve_title("sum"),
+            Err(CatalogError::AmbiguousProblem { .. })
+        ));
+        assert!(matches!(
+            catalog.resolve_title("median of two sorted arrays"),
+            Err(CatalogError::ProblemNotFound(_))
+        ));
+    }
+}
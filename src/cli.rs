@@ -1,22 +1,218 @@
 use std::path::PathBuf;
 
-use clap::Parser;
-use url::Url;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::crawler::leetcode::ProblemQuery;
+use crate::crawler::leetcode::daily::ChallengeDate;
+use crate::crawler::leetcode::problemset::QuestionStatus;
+use crate::crawler::leetcode::question::QuestionDifficulty;
 
 #[derive(Debug, Parser)]
 #[clap(author, version, about = "LeetCode → Anki .apkg")]
 pub struct Cli {
-    /// Problem URL (e.g., https://leetcode.com/problems/two-sum/)
-    #[arg(short, long)]
-    pub url: Url,
-
     /// Config file path
-    #[arg(short, long, default_value = "config.json", value_name = "CONFIG_FILE")]
+    #[arg(
+        short,
+        long,
+        global = true,
+        default_value = "config.json",
+        value_name = "CONFIG_FILE"
+    )]
     pub config: PathBuf,
 
     /// Output directory
-    #[arg(short, long, default_value = "output")]
+    #[arg(short, long, global = true, default_value = "output")]
     pub output_dir: PathBuf,
+
+    /// Read the LeetCode cookies from this Firefox or Chromium profile directory. Not allowed with
+    /// more than one profile
+    #[arg(long, global = true, value_name = "PROFILE_DIR")]
+    pub browser_profile: Option<PathBuf>,
+
+    /// Use a named profile from the config file. Repeat it to merge the submissions of several
+    /// accounts into one deck; the first profile provides the deck settings
+    #[arg(long = "profile", global = true, value_name = "NAME")]
+    pub profiles: Vec<String>,
+
+    /// Save a screenshot, the HTML and the URL of pages where no submission code was found into
+    /// `debug/`
+    #[arg(long, global = true)]
+    pub debug_artifacts: bool,
+
+    /// Ignore the configured cookie and only export public data (no submissions)
+    #[arg(long, global = true)]
+    pub anonymous: bool,
+
+    /// Log output format
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Append the logs to this file instead of printing them
+    #[arg(long, global = true, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Write the run summary of fetch, contest and daily to this JSON file. Otherwise it is only
+    /// printed when standard error is a terminal
+    #[arg(long, global = true, value_name = "PATH")]
+    pub report: Option<PathBuf>,
+
+    /// Add or update the notes in a running Anki through AnkiConnect instead of writing an .apkg
+    #[arg(long, global = true)]
+    pub anki_connect: bool,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Fetch problems by URL, id, slug or title, or every problem of a tag
+    Fetch(FetchArgs),
+    /// Fetch every problem of a contest with your in-contest submissions
+    Contest(ContestArgs),
+    /// Fetch the daily coding challenge
+    Daily(DailyArgs),
+    /// Manage the local problem catalog
+    #[command(subcommand)]
+    Catalog(CatalogCommand),
+    /// Manage the LeetCode session
+    #[command(subcommand)]
+    Auth(AuthCommand),
+    /// Manage the WebDriver
+    #[command(subcommand, name = "webdriver")]
+    WebDriver(WebDriverCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum WebDriverCommand {
+    /// Delete the browser sessions that interrupted runs left open on the WebDriver endpoint
+    Cleanup,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuthCommand {
+    /// Sign in through a browser window and save the session cookies into the config
+    Login(LoginArgs),
+    /// Check that the configured session is valid and show when it expires
+    Status,
+}
+
+#[derive(Debug, Args)]
+pub struct LoginArgs {
+    /// How long to wait for the sign-in to complete, in seconds
+    #[arg(long, default_value_t = 300)]
+    pub timeout: u64,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CatalogCommand {
+    /// Download the full problemset metadata into the local catalog
+    Update,
+    /// Search the local catalog offline
+    Search(CatalogSearchArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct CatalogSearchArgs {
+    /// Frontend id, slug or words of the title to search for
+    pub query: Option<String>,
+
+    /// Only show problems with this topic tag slug
+    #[arg(short, long)]
+    pub tag: Option<String>,
+
+    /// Only show problems of this difficulty
+    #[arg(short, long, value_enum)]
+    pub difficulty: Option<DifficultyArg>,
+
+    /// Only show problems with this status
+    #[arg(short, long, value_enum)]
+    pub status: Option<StatusArg>,
+
+    /// Hide premium-only problems
+    #[arg(long)]
+    pub free_only: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct DailyArgs {
+    /// Challenge date in YYYY-MM-DD format (UTC), defaults to today
+    #[arg(long)]
+    pub date: Option<ChallengeDate>,
+
+    /// Fetch the challenge again even if it was already exported
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ContestArgs {
+    /// Contest slug (e.g., weekly-contest-400 or biweekly-contest-130)
+    pub slug: String,
+}
+
+#[derive(Debug, Args)]
+pub struct FetchArgs {
+    /// Problems to fetch: a problem or submission URL, a frontend id (e.g., 1), a slug
+    /// (e.g., two-sum) or part of a title (e.g., "two sum")
+    #[arg(
+        value_name = "PROBLEM",
+        required_unless_present = "tag",
+        conflicts_with = "tag"
+    )]
+    pub problems: Vec<ProblemQuery>,
+
+    /// Topic tag slug (e.g., dynamic-programming)
+    #[arg(short, long)]
+    pub tag: Option<String>,
+
+    /// Only fetch problems of this difficulty (requires --tag)
+    #[arg(short, long, value_enum, requires = "tag")]
+    pub difficulty: Option<DifficultyArg>,
+
+    /// Only fetch problems with this status (requires --tag)
+    #[arg(short, long, value_enum, requires = "tag")]
+    pub status: Option<StatusArg>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DifficultyArg {
+    Easy,
+    Medium,
+    Hard,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum StatusArg {
+    Solved,
+    Attempted,
+    Todo,
+}
+
+impl From<DifficultyArg> for QuestionDifficulty {
+    fn from(difficulty: DifficultyArg) -> Self {
+        match difficulty {
+            DifficultyArg::Easy => QuestionDifficulty::Easy,
+            DifficultyArg::Medium => QuestionDifficulty::Medium,
+            DifficultyArg::Hard => QuestionDifficulty::Hard,
+        }
+    }
+}
+
+impl From<StatusArg> for QuestionStatus {
+    fn from(status: StatusArg) -> Self {
+        match status {
+            StatusArg::Solved => QuestionStatus::Solved,
+            StatusArg::Attempted => QuestionStatus::Attempted,
+            StatusArg::Todo => QuestionStatus::Todo,
+        }
+    }
 }
 
 pub fn parse_args() -> Cli {
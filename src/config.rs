@@ -1,7 +1,15 @@
+use crate::auth::browser::{BrowserKind, import_cookies};
+use crate::auth::credentials::{
+    COOKIE_ENV_VAR, Credentials, read_cookie_file, warn_if_world_readable,
+};
+use crate::crawler::leetcode::LeetCodeSite;
+use crate::crawler::web_driver::WebDriverBrowser;
 use crate::error::ConfigParseError;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 use std::fmt::Formatter;
+use std::path::PathBuf;
 use std::str::FromStr;
 use url::Url;
 
@@ -9,9 +17,60 @@ use url::Url;
 pub struct ConfigFile {
     #[serde(default)]
     pub anki: AnkiConfig,
-    pub rate_limit: u32,
+    /// Requests per second for both API requests and page loads, unless `rate_limits` overrides them.
+    pub rate_limit: f64,
+    #[serde(default)]
+    pub rate_limits: RateLimitsConfig,
     pub web_driver: WebDriverConfig,
-    pub cookie: String,
+    #[serde(default)]
+    pub site: LeetCodeSite,
+    /// Without a cookie, lanci runs in anonymous mode and only exports public data.
+    #[serde(default)]
+    pub cookie: Option<String>,
+    /// File containing the raw `Cookie` header, used instead of `cookie`.
+    #[serde(default)]
+    pub cookie_file: Option<PathBuf>,
+    /// JSON credentials file holding the cookie, used instead of `cookie`.
+    #[serde(default)]
+    pub credentials_file: Option<PathBuf>,
+    /// Read the cookies from a local browser profile instead of `cookie`.
+    #[serde(default)]
+    pub browser_profile: Option<BrowserProfileConfig>,
+    #[serde(default)]
+    pub scraping: ScrapingConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub premium_only: PremiumOnlyPolicy,
+    /// Write the cookies refreshed by LeetCode back to `credentials_file` after each run.
+    #[serde(default)]
+    pub persist_refreshed_cookies: bool,
+    #[serde(default = "default_catalog_path")]
+    pub catalog_path: PathBuf,
+    /// Named accounts, selected with `--profile`.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+/// Settings of one named account. Unset fields fall back to the top-level settings, except for the
+/// cookie sources: as soon as a profile sets one of them, the top-level ones are ignored. Other
+/// settings, such as `web_driver`, are shared by all profiles and rejected inside one.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    #[serde(default)]
+    pub site: Option<LeetCodeSite>,
+    /// Anki settings merged key by key into the top-level `anki` settings.
+    #[serde(default)]
+    pub anki: Option<serde_json::Value>,
+    #[serde(default)]
+    pub cookie: Option<String>,
+    #[serde(default)]
+    pub cookie_file: Option<PathBuf>,
+    #[serde(default)]
+    pub credentials_file: Option<PathBuf>,
+    #[serde(default)]
+    pub browser_profile: Option<BrowserProfileConfig>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -20,6 +79,18 @@ pub struct AnkiConfig {
     pub model: AnkiModelConfig,
     #[serde(default)]
     pub deck: AnkiDeckConfig,
+    /// Where notes are pushed with `--anki-connect`.
+    #[serde(default)]
+    pub connect: AnkiConnectConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnkiConnectConfig {
+    #[serde(default = "default_anki_connect_endpoint")]
+    pub endpoint: Url,
+    /// API key, when AnkiConnect is set up to require one.
+    #[serde(default)]
+    pub api_key: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -36,17 +107,162 @@ pub struct AnkiDeckConfig {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WebDriverConfig {
+    #[serde(default = "default_web_driver_endpoint")]
     pub endpoint: Url,
+    /// geckodriver or chromedriver binary to start on a free port; `endpoint` is then ignored.
+    #[serde(default)]
+    pub driver: Option<PathBuf>,
     #[serde(default)]
     pub headless: bool,
+    #[serde(default)]
+    pub browser: WebDriverBrowser,
+    /// Browser executable, when the driver cannot find it on its own.
+    #[serde(default)]
+    pub binary: Option<PathBuf>,
+    /// Browser profile directory to start the browser with.
+    #[serde(default)]
+    pub user_data_dir: Option<PathBuf>,
+    /// Extra command line arguments for the browser.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra WebDriver capabilities, merged into the generated ones.
+    #[serde(default)]
+    pub capabilities: serde_json::Map<String, serde_json::Value>,
+}
+
+/// What to do with premium-only problems when the session has no LeetCode Premium subscription.
+/// With a subscription, their full description is exported like any other problem.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PremiumOnlyPolicy {
+    /// Skip the problem with a warning.
+    #[default]
+    Skip,
+    /// Export a card with the metadata and the own submissions, without the description.
+    Stub,
+}
+
+/// Separate rate limits for GraphQL API requests and submission page loads in the browser.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RateLimitsConfig {
+    #[serde(default)]
+    pub graphql: Option<RateLimit>,
+    #[serde(default)]
+    pub web_driver: Option<RateLimit>,
+}
+
+/// The rate limits in effect, with the top-level `rate_limit` filled in where none is configured.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimits {
+    pub graphql: RateLimit,
+    pub web_driver: RateLimit,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Requests per second. Fractions allow slower rates, e.g. `0.25` for one request every four seconds.
+    pub requests_per_second: f64,
+    /// How many requests may be sent at once after a quiet period.
+    #[serde(default = "default_burst")]
+    pub burst: u32,
+    /// Random delay added before each request, between these bounds, in milliseconds.
+    #[serde(default = "default_jitter_min_ms")]
+    pub jitter_min_ms: u64,
+    #[serde(default = "default_jitter_max_ms")]
+    pub jitter_max_ms: u64,
+}
+
+impl RateLimit {
+    /// A rate limit with the default burst and jitter.
+    pub fn per_second(requests_per_second: f64) -> Self {
+        Self {
+            requests_per_second,
+            burst: default_burst(),
+            jitter_min_ms: default_jitter_min_ms(),
+            jitter_max_ms: default_jitter_max_ms(),
+        }
+    }
+}
+
+/// Proxy, TLS and HTTP settings for the connections to LeetCode, from both the HTTP client and the
+/// browser.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Proxy for all traffic, e.g. `http://proxy.example.com:3128` or `socks5://localhost:1080`.
+    #[serde(default)]
+    pub proxy: Option<Url>,
+    /// PEM files with extra CA certificates to trust, e.g. the one of a TLS-intercepting proxy. The
+    /// browser accepts any certificate instead when this is set.
+    #[serde(default)]
+    pub ca_certificates: Vec<PathBuf>,
+    /// Timeout for establishing a connection, in seconds.
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+    /// Timeout for a whole request, or a page load in the browser, in seconds.
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// User agent for the HTTP client and the browser. The browser keeps its own when unset.
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// Extra headers sent with every API request, and every browser request in Chrome and Chromium.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// How submitted code is read from the submission page in the browser.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrapingConfig {
+    /// How many times the strategies are tried before giving up on a page.
+    #[serde(default = "default_poll_attempts")]
+    pub poll_attempts: u32,
+    /// Delay between two attempts, in milliseconds.
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// Extraction strategies, tried in order on each attempt until one returns code.
+    #[serde(default = "default_extraction_strategies")]
+    pub strategies: Vec<ExtractionStrategy>,
+    /// How long to wait for the user to solve a Cloudflare or captcha challenge in a visible
+    /// browser, in seconds.
+    #[serde(default = "default_challenge_timeout_secs")]
+    pub challenge_timeout_secs: u64,
+    /// When set, a screenshot, the HTML and the URL of pages where no code was found are saved here.
+    #[serde(default)]
+    pub debug_artifacts_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ExtractionStrategy {
+    /// Runs a JavaScript function body whose return value is the code, or `null`.
+    Script { script: String },
+    /// Reads the text of the first element matching a CSS selector.
+    Selector { selector: String },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BrowserProfileConfig {
+    /// Profile directory, or the cookie database file itself.
+    pub path: PathBuf,
+    /// Browser family, guessed from the files in `path` when omitted.
+    #[serde(default)]
+    pub browser: Option<BrowserKind>,
 }
 
 #[derive(Debug)]
 pub struct Config {
+    /// Name of the selected profile, if any.
+    pub profile: Option<String>,
     pub anki: AnkiConfig,
-    pub rate_limit: u32,
+    pub rate_limits: RateLimits,
     pub web_driver: WebDriverConfig,
-    pub cookie: LeetCodeCookies,
+    pub site: LeetCodeSite,
+    pub cookie: Option<LeetCodeCookies>,
+    pub credentials_file: Option<PathBuf>,
+    pub persist_refreshed_cookies: bool,
+    pub scraping: ScrapingConfig,
+    pub network: NetworkConfig,
+    pub premium_only: PremiumOnlyPolicy,
+    pub catalog_path: PathBuf,
 }
 
 #[derive(Debug, Clone)]
@@ -97,24 +313,414 @@ impl fmt::Display for LeetCodeCookies {
     }
 }
 
-impl Config {
-    pub async fn load_from_file(path: &str) -> Result<Config, ConfigParseError> {
+impl ConfigFile {
+    pub async fn load_from_file(path: &str) -> Result<ConfigFile, ConfigParseError> {
         let content = tokio::fs::read_to_string(path).await?;
         let config_file: ConfigFile = serde_json::from_str(&content)?;
 
+        Ok(config_file)
+    }
+
+    /// Stores the cookie in the configuration file, at the top level or in the given profile. Only
+    /// the `cookie` value is replaced in the file text, so the formatting, key order and file
+    /// permissions of a hand-written config are kept.
+    pub async fn save_cookie(
+        path: &str,
+        profile: Option<&str>,
+        cookie: &LeetCodeCookies,
+    ) -> Result<(), ConfigParseError> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let content = set_cookie_in_json(&content, profile, &cookie.to_string())?;
+        tokio::fs::write(path, content)
+            .await
+            .map_err(ConfigParseError::WriteFileError)?;
+        warn_if_world_readable(std::path::Path::new(path));
+
+        Ok(())
+    }
+}
+
+/// Sets the `cookie` member of the top-level object or of `profiles.<profile>` in a JSON document,
+/// leaving the rest of the text as it is. A missing `cookie` member is added as the first one.
+fn set_cookie_in_json(
+    content: &str,
+    profile: Option<&str>,
+    cookie: &str,
+) -> Result<String, ConfigParseError> {
+    let not_an_object = || {
+        ConfigParseError::DeserializeError(serde::de::Error::custom(
+            "configuration file must contain a JSON object",
+        ))
+    };
+    // Validated first, so that the scanner below only ever sees well-formed JSON
+    serde_json::from_str::<serde_json::Value>(content)?;
+    let bytes = content.as_bytes();
+
+    let mut object_start = skip_json_whitespace(bytes, 0);
+    if bytes[object_start] != b'{' {
+        return Err(not_an_object());
+    }
+    if let Some(profile) = profile {
+        let unknown_profile = || ConfigParseError::UnknownProfile(profile.to_string());
+        let (_, profiles_start, _) = json_object_members(content, object_start)
+            .into_iter()
+            .find(|(key, _, _)| key == "profiles")
+            .ok_or_else(unknown_profile)?;
+        let (_, profile_start, _) = json_object_members(content, profiles_start)
+            .into_iter()
+            .find(|(key, _, _)| key == profile)
+            .ok_or_else(unknown_profile)?;
+        if bytes[profile_start] != b'{' {
+            return Err(not_an_object());
+        }
+        object_start = profile_start;
+    }
+
+    let cookie_value = serde_json::to_string(cookie)?;
+    let members = json_object_members(content, object_start);
+    let edited = match members.iter().find(|(key, _, _)| key == "cookie") {
+        Some((_, value_start, value_end)) => format!(
+            "{}{}{}",
+            &content[..*value_start],
+            cookie_value,
+            &content[*value_end..]
+        ),
+        None => {
+            // Inserted before the first member, with the same indentation
+            let first_member = skip_json_whitespace(bytes, object_start + 1);
+            let separator = if members.is_empty() {
+                ""
+            } else {
+                &content[object_start + 1..first_member]
+            };
+            let comma = if members.is_empty() { "" } else { "," };
+            format!(
+                "{}\"cookie\": {}{}{}{}",
+                &content[..first_member],
+                cookie_value,
+                comma,
+                separator,
+                &content[first_member..]
+            )
+        }
+    };
+
+    Ok(edited)
+}
+
+fn skip_json_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    pos
+}
+
+/// Returns the end of the JSON value starting at `pos`, which must be well-formed.
+fn skip_json_value(bytes: &[u8], pos: usize) -> usize {
+    match bytes[pos] {
+        b'"' => {
+            let mut end = pos + 1;
+            while bytes[end] != b'"' {
+                end += if bytes[end] == b'\\' { 2 } else { 1 };
+            }
+            end + 1
+        }
+        b'{' | b'[' => {
+            let mut depth = 0;
+            let mut end = pos;
+            loop {
+                match bytes[end] {
+                    b'"' => {
+                        end = skip_json_value(bytes, end);
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return end + 1;
+                        }
+                    }
+                    _ => {}
+                }
+                end += 1;
+            }
+        }
+        _ => {
+            let mut end = pos;
+            while end < bytes.len()
+                && !matches!(bytes[end], b',' | b'}' | b']')
+                && !bytes[end].is_ascii_whitespace()
+            {
+                end += 1;
+            }
+            end
+        }
+    }
+}
+
+/// Lists the members of the well-formed JSON object starting at `object_start`, as the decoded key
+/// and the start and end of the value.
+fn json_object_members(content: &str, object_start: usize) -> Vec<(String, usize, usize)> {
+    let bytes = content.as_bytes();
+    let mut members = Vec::new();
+    let mut pos = skip_json_whitespace(bytes, object_start + 1);
+    while bytes[pos] == b'"' {
+        let key_end = skip_json_value(bytes, pos);
+        let key = serde_json::from_str(&content[pos..key_end]).unwrap_or_default();
+        let colon = skip_json_whitespace(bytes, key_end);
+        let value_start = skip_json_whitespace(bytes, colon + 1);
+        let value_end = skip_json_value(bytes, value_start);
+        members.push((key, value_start, value_end));
+
+        pos = skip_json_whitespace(bytes, value_end);
+        if bytes[pos] == b',' {
+            pos = skip_json_whitespace(bytes, pos + 1);
+        }
+    }
+
+    members
+}
+
+impl ConfigFile {
+    /// Applies the named profile on top of the top-level settings.
+    pub fn select_profile(mut self, name: &str) -> Result<ConfigFile, ConfigParseError> {
+        let profile = self
+            .profiles
+            .remove(name)
+            .ok_or_else(|| ConfigParseError::UnknownProfile(name.to_string()))?;
+
+        if profile.cookie.is_some()
+            || profile.cookie_file.is_some()
+            || profile.credentials_file.is_some()
+            || profile.browser_profile.is_some()
+        {
+            self.cookie = profile.cookie;
+            self.cookie_file = profile.cookie_file;
+            self.credentials_file = profile.credentials_file;
+            self.browser_profile = profile.browser_profile;
+        }
+        if let Some(site) = profile.site {
+            self.site = site;
+        }
+        if let Some(anki) = profile.anki {
+            let mut merged = serde_json::to_value(&self.anki)?;
+            merge_json(&mut merged, anki);
+            self.anki = serde_json::from_value(merged)?;
+        }
+
+        Ok(self)
+    }
+
+    /// Returns the API and browser rate limits, falling back to `rate_limit` for unset ones.
+    pub fn rate_limits(&self) -> RateLimits {
+        let default_rate_limit = RateLimit::per_second(self.rate_limit);
+
+        RateLimits {
+            graphql: self
+                .rate_limits
+                .graphql
+                .clone()
+                .unwrap_or_else(|| default_rate_limit.clone()),
+            web_driver: self
+                .rate_limits
+                .web_driver
+                .clone()
+                .unwrap_or(default_rate_limit),
+        }
+    }
+
+    /// Resolves the session cookie from the first configured source, in order: the `LANCI_COOKIE`
+    /// environment variable (passed as `env_cookie`), the browser profile, the cookie file, the
+    /// credentials file and finally the inline `cookie`.
+    pub fn resolve_cookie(
+        &self,
+        env_cookie: Option<String>,
+    ) -> Result<Option<LeetCodeCookies>, ConfigParseError> {
+        if let Some(cookie) = env_cookie.filter(|cookie| !cookie.trim().is_empty()) {
+            return LeetCodeCookies::from_str(&cookie).map(Some);
+        }
+
+        if let Some(profile) = &self.browser_profile {
+            return Ok(Some(import_cookies(
+                &profile.path,
+                profile.browser,
+                self.site,
+            )?));
+        }
+
+        if let Some(cookie_file) = &self.cookie_file {
+            return LeetCodeCookies::from_str(&read_cookie_file(cookie_file)?).map(Some);
+        }
+
+        if let Some(credentials_file) = &self.credentials_file
+            && let Some(cookie) = Credentials::load_from_file(credentials_file)?.cookie
+        {
+            return LeetCodeCookies::from_str(&cookie).map(Some);
+        }
+
+        self.cookie
+            .as_deref()
+            .map(LeetCodeCookies::from_str)
+            .transpose()
+    }
+}
+
+impl Config {
+    pub async fn load_from_file(path: &str) -> Result<Config, ConfigParseError> {
+        let config_file = ConfigFile::load_from_file(path).await?;
+
         Config::try_from(config_file)
     }
+
+    /// Loads the configuration with the named profile applied. The profile reads its cookie from
+    /// the `LANCI_COOKIE_<NAME>` environment variable instead of `LANCI_COOKIE`.
+    pub async fn load_profile_from_file(
+        path: &str,
+        profile: &str,
+    ) -> Result<Config, ConfigParseError> {
+        let config_file = ConfigFile::load_from_file(path)
+            .await?
+            .select_profile(profile)?;
+        let env_cookie = std::env::var(profile_cookie_env_var(profile)).ok();
+
+        let mut config = Config::from_config_file(config_file, env_cookie)?;
+        config.profile = Some(profile.to_string());
+
+        Ok(config)
+    }
+
+    fn from_config_file(
+        config_file: ConfigFile,
+        env_cookie: Option<String>,
+    ) -> Result<Config, ConfigParseError> {
+        let cookie = config_file.resolve_cookie(env_cookie)?;
+        let rate_limits = config_file.rate_limits();
+
+        Ok(Self {
+            profile: None,
+            anki: config_file.anki,
+            rate_limits,
+            web_driver: config_file.web_driver,
+            site: config_file.site,
+            cookie,
+            credentials_file: config_file.credentials_file,
+            persist_refreshed_cookies: config_file.persist_refreshed_cookies,
+            scraping: config_file.scraping,
+            network: config_file.network,
+            premium_only: config_file.premium_only,
+            catalog_path: config_file.catalog_path,
+        })
+    }
+}
+
+/// Name of the environment variable holding the cookie of a profile, e.g. `LANCI_COOKIE_WORK`.
+pub fn profile_cookie_env_var(profile: &str) -> String {
+    format!(
+        "{}_{}",
+        COOKIE_ENV_VAR,
+        profile
+            .to_uppercase()
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+    )
+}
+
+fn default_poll_attempts() -> u32 {
+    20
+}
+
+fn default_poll_interval_ms() -> u64 {
+    500
+}
+
+/// Recursively merges `overlay` into `base`. Objects are merged key by key, other values replace
+/// the value in `base`.
+fn merge_json(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge_json(base_value, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn default_anki_connect_endpoint() -> Url {
+    Url::parse("http://127.0.0.1:8765").expect("default AnkiConnect endpoint is a valid URL")
+}
+
+fn default_burst() -> u32 {
+    1
+}
+
+fn default_jitter_min_ms() -> u64 {
+    200
+}
+
+fn default_jitter_max_ms() -> u64 {
+    700
+}
+
+fn default_challenge_timeout_secs() -> u64 {
+    300
+}
+
+fn default_extraction_strategies() -> Vec<ExtractionStrategy> {
+    vec![
+        ExtractionStrategy::Script {
+            script: "return typeof pageData !== 'undefined' ? pageData.submissionCode : null;"
+                .to_string(),
+        },
+        ExtractionStrategy::Selector {
+            selector: "pre code".to_string(),
+        },
+    ]
+}
+
+fn default_web_driver_endpoint() -> Url {
+    Url::parse("http://localhost:4444").expect("default endpoint is a valid URL")
+}
+
+fn default_catalog_path() -> PathBuf {
+    PathBuf::from("catalog.json")
+}
+
+impl Default for ScrapingConfig {
+    fn default() -> Self {
+        Self {
+            poll_attempts: default_poll_attempts(),
+            poll_interval_ms: default_poll_interval_ms(),
+            strategies: default_extraction_strategies(),
+            challenge_timeout_secs: default_challenge_timeout_secs(),
+            debug_artifacts_dir: None,
+        }
+    }
 }
 
 impl Default for AnkiModelConfig {
     fn default() -> Self {
         Self {
-            id: 1307111927,
+            id: crate::anki::MODEL_ID,
             name: String::from("LeetCode"),
         }
     }
 }
 
+impl Default for AnkiConnectConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: default_anki_connect_endpoint(),
+            api_key: None,
+        }
+    }
+}
+
 impl Default for AnkiDeckConfig {
     fn default() -> Self {
         Self {
@@ -128,12 +734,7 @@ impl TryFrom<ConfigFile> for Config {
     type Error = ConfigParseError;
 
     fn try_from(config_file: ConfigFile) -> Result<Self, Self::Error> {
-        Ok(Self {
-            anki: config_file.anki,
-            rate_limit: config_file.rate_limit,
-            web_driver: config_file.web_driver,
-            cookie: LeetCodeCookies::from_str(&config_file.cookie)?,
-        })
+        Config::from_config_file(config_file, std::env::var(COOKIE_ENV_VAR).ok())
     }
 }
 
@@ -141,6 +742,199 @@ impl TryFrom<ConfigFile> for Config {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_config_without_cookie_is_anonymous() {
+        let config_file: ConfigFile = serde_json::from_str(
+            r#"{"rate_limit": 1, "web_driver": {"endpoint": "http://localhost:4444"}}"#,
+        )
+        .unwrap();
+        let config = Config::from_config_file(config_file, None).unwrap();
+
+        assert!(config.cookie.is_none());
+    }
+
+    #[test]
+    fn test_rate_limits_fall_back_to_rate_limit() {
+        let config_file: ConfigFile = serde_json::from_str(
+            r#"{
+                "rate_limit": 0.5,
+                "rate_limits": {"web_driver": {"requests_per_second": 0.2, "burst": 2, "jitter_max_ms": 3000}},
+                "web_driver": {"endpoint": "http://localhost:4444"}
+            }"#,
+        )
+        .unwrap();
+        let config = Config::from_config_file(config_file, None).unwrap();
+
+        assert_eq!(config.rate_limits.graphql, RateLimit::per_second(0.5));
+        assert_eq!(
+            config.rate_limits.web_driver,
+            RateLimit {
+                requests_per_second: 0.2,
+                burst: 2,
+                jitter_min_ms: 200,
+                jitter_max_ms: 3000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_resolve_cookie_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let cookie_file = dir.path().join("cookie.txt");
+        std::fs::write(&cookie_file, "csrftoken=file; LEETCODE_SESSION=file").unwrap();
+        let credentials_file = dir.path().join("credentials.json");
+        Credentials {
+            cookie: Some("csrftoken=credentials; LEETCODE_SESSION=credentials".to_string()),
+        }
+        .save_to_file(&credentials_file)
+        .unwrap();
+
+        let mut config_file: ConfigFile = serde_json::from_str(
+            r#"{"rate_limit": 1, "web_driver": {"endpoint": "http://localhost:4444"}, "cookie": "csrftoken=inline; LEETCODE_SESSION=inline"}"#,
+        )
+        .unwrap();
+        let csrf_token = |config_file: &ConfigFile, env_cookie: Option<&str>| {
+            config_file
+                .resolve_cookie(env_cookie.map(str::to_string))
+                .unwrap()
+                .unwrap()
+                .csrf_token
+        };
+
+        assert_eq!(csrf_token(&config_file, None), "inline");
+        config_file.credentials_file = Some(credentials_file);
+        assert_eq!(csrf_token(&config_file, None), "credentials");
+        config_file.cookie_file = Some(cookie_file);
+        assert_eq!(csrf_token(&config_file, None), "file");
+        assert_eq!(
+            csrf_token(&config_file, Some("csrftoken=env; LEETCODE_SESSION=env")),
+            "env"
+        );
+    }
+
+    #[test]
+    fn test_select_profile_overrides_top_level_settings() {
+        let config_file = || -> ConfigFile {
+            serde_json::from_str(
+                r#"{
+                "rate_limit": 1,
+                "web_driver": {"endpoint": "http://localhost:4444"},
+                "cookie_file": "personal.txt",
+                "anki": {
+                    "model": {"id": 1, "name": "Problems"},
+                    "deck": {"id": 7, "name": "LeetCode"},
+                    "connect": {"api_key": "secret"}
+                },
+                "profiles": {
+                    "cn": {
+                        "site": "cn",
+                        "cookie": "csrftoken=cn; LEETCODE_SESSION=cn",
+                        "anki": {"deck": {"name": "LeetCode CN"}}
+                    },
+                    "plain": {}
+                }
+            }"#,
+            )
+            .unwrap()
+        };
+
+        let cn = config_file().select_profile("cn").unwrap();
+        assert_eq!(cn.site, LeetCodeSite::Cn);
+        assert!(cn.cookie_file.is_none());
+        assert_eq!(cn.anki.deck.name, "LeetCode CN");
+        assert_eq!(cn.anki.deck.id, 7);
+        assert_eq!(cn.anki.model.name, "Problems");
+        assert_eq!(cn.anki.connect.api_key.as_deref(), Some("secret"));
+        assert_eq!(cn.resolve_cookie(None).unwrap().unwrap().csrf_token, "cn");
+
+        let plain = config_file().select_profile("plain").unwrap();
+        assert_eq!(plain.site, LeetCodeSite::Com);
+        assert_eq!(plain.cookie_file, Some(PathBuf::from("personal.txt")));
+        assert!(matches!(
+            config_file().select_profile("missing"),
+            Err(ConfigParseError::UnknownProfile(_))
+        ));
+    }
+
+    #[test]
+    fn test_profile_cannot_set_shared_settings() {
+        let result = serde_json::from_str::<ConfigFile>(
+            r#"{
+                "profiles": {
+                    "work": {"web_driver": {"endpoint": "http://localhost:9515"}}
+                }
+            }"#,
+        );
+
+        assert!(result.unwrap_err().to_string().contains("web_driver"));
+    }
+
+    #[test]
+    fn test_set_cookie_in_json_keeps_the_rest_of_the_file() {
+        let content = r#"{
+    "rate_limit": 1,
+    "cookie": "csrftoken=old; LEETCODE_SESSION=old",
+    "profiles": {
+        "work": { "site": "cn" },
+        "empty": {}
+    }
+}
+"#;
+
+        assert_eq!(
+            set_cookie_in_json(content, None, "csrftoken=new").unwrap(),
+            content.replace("csrftoken=old; LEETCODE_SESSION=old", "csrftoken=new")
+        );
+        assert_eq!(
+            set_cookie_in_json(content, Some("work"), "a\"b").unwrap(),
+            content.replace(
+                r#"{ "site": "cn" }"#,
+                r#"{ "cookie": "a\"b", "site": "cn" }"#
+            )
+        );
+        assert_eq!(
+            set_cookie_in_json(content, Some("empty"), "c").unwrap(),
+            content.replace(r#""empty": {}"#, r#""empty": {"cookie": "c"}"#)
+        );
+        assert_eq!(
+            set_cookie_in_json("{\n  \"site\": \"com\"\n}", None, "c").unwrap(),
+            "{\n  \"cookie\": \"c\",\n  \"site\": \"com\"\n}"
+        );
+        assert!(matches!(
+            set_cookie_in_json(content, Some("missing"), "c"),
+            Err(ConfigParseError::UnknownProfile(_))
+        ));
+        assert!(set_cookie_in_json("[]", None, "c").is_err());
+    }
+
+    #[test]
+    fn test_scraping_config_defaults_and_overrides() {
+        let scraping: ScrapingConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(scraping.poll_attempts, 20);
+        assert_eq!(scraping.strategies.len(), 2);
+
+        let scraping: ScrapingConfig = serde_json::from_str(
+            r#"{"poll_interval_ms": 250, "strategies": [{"type": "selector", "selector": ".code"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(scraping.poll_interval_ms, 250);
+        assert_eq!(
+            scraping.strategies,
+            vec![ExtractionStrategy::Selector {
+                selector: ".code".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_profile_cookie_env_var() {
+        assert_eq!(profile_cookie_env_var("work"), "LANCI_COOKIE_WORK");
+        assert_eq!(
+            profile_cookie_env_var("leetcode-cn"),
+            "LANCI_COOKIE_LEETCODE_CN"
+        );
+    }
+
     #[test]
     fn test_parse_valid_leetcode_cookies_from_str() {
         // Test valid cookie string
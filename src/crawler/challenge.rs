@@ -0,0 +1,68 @@
+/// Markers found only on Cloudflare interstitial pages. Script paths such as
+/// `/cdn-cgi/challenge-platform/` and captcha class names are left out, because Cloudflare and
+/// LeetCode add them to regular pages too.
+const CHALLENGE_MARKERS: [&str; 6] = [
+    "<title>just a moment...</title>",
+    "attention required! | cloudflare",
+    "id=\"challenge-form\"",
+    "id=\"challenge-stage\"",
+    "cf_chl_",
+    "cf-turnstile",
+];
+
+/// Returns `true` if the HTML is a Cloudflare or captcha challenge page rather than LeetCode content.
+pub fn is_challenge_page(html: &str) -> bool {
+    let html = html.to_lowercase();
+
+    CHALLENGE_MARKERS.iter().any(|marker| html.contains(marker))
+}
+
+/// Returns `true` if an HTTP response is a challenge page, which is always served as HTML.
+pub fn is_challenge_response(content_type: &str, body: &[u8]) -> bool {
+    content_type.contains("text/html") && is_challenge_page(&String::from_utf8_lossy(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_cloudflare_interstitial() {
+        let html = r#"<!DOCTYPE html><html><head><title>Just a moment...</title></head>
+            <body><div id="challenge-stage"></div>
+            <script src="/cdn-cgi/challenge-platform/h/g/orchestrate/chl_page/v1"></script></body></html>"#;
+
+        assert!(is_challenge_page(html));
+        assert!(is_challenge_response(
+            "text/html; charset=UTF-8",
+            html.as_bytes()
+        ));
+        assert!(!is_challenge_response("application/json", html.as_bytes()));
+    }
+
+    #[test]
+    fn test_regular_pages_are_not_challenges() {
+        assert!(!is_challenge_page(
+            "<html><head><title>Two Sum - LeetCode</title></head><body><pre><code>fn main() {}</code></pre></body></html>"
+        ));
+        assert!(!is_challenge_response(
+            "application/json",
+            br#"{"data":{"question":null}}"#
+        ));
+    }
+
+    #[test]
+    fn test_regular_page_with_cloudflare_script_is_not_a_challenge() {
+        let html = r#"<!DOCTYPE html><html><head><title>Submission Detail - LeetCode</title></head>
+            <body><div class="g-recaptcha-badge"></div><pre><code>fn main() {}</code></pre>
+            <script>(function(){var a=document.createElement('script');
+            a.src='/cdn-cgi/challenge-platform/scripts/jsd/main.js';document.head.appendChild(a);})();</script>
+            </body></html>"#;
+
+        assert!(!is_challenge_page(html));
+        assert!(!is_challenge_response(
+            "text/html; charset=UTF-8",
+            html.as_bytes()
+        ));
+    }
+}
@@ -0,0 +1,60 @@
+use super::deserialize_vec_or_default;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct ContestObj {
+    pub data: ContestData,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestData {
+    pub contest: ContestDetail,
+    #[serde(default, deserialize_with = "deserialize_vec_or_default")]
+    pub contest_question_list: Vec<ContestQuestion>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestDetail {
+    pub title: String,
+    pub title_slug: String,
+    /// Unix timestamp (in seconds) when the contest started.
+    pub start_time: i64,
+    /// Contest duration in seconds.
+    pub duration: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestQuestion {
+    pub title: String,
+    pub title_slug: String,
+}
+
+impl ContestDetail {
+    /// Unix timestamp (in seconds) when the contest ended.
+    pub fn end_time(&self) -> i64 {
+        self.start_time + self.duration
+    }
+
+    /// The contest title as an Anki tag, which cannot contain spaces (e.g. `Weekly_Contest_400`).
+    pub fn tag(&self) -> String {
+        self.title.split_whitespace().collect::<Vec<_>>().join("_")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contest_tag_uses_title() {
+        let contest: ContestDetail = serde_json::from_str(
+            r#"{"title":"Weekly Contest 400","titleSlug":"weekly-contest-400","startTime":1716690600,"duration":5400}"#,
+        )
+        .unwrap();
+
+        assert_eq!(contest.tag(), "Weekly_Contest_400");
+    }
+}
@@ -0,0 +1,166 @@
+use super::deserialize_vec_or_default;
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Deserialize)]
+pub struct ActiveDailyObj {
+    pub data: ActiveDailyData,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveDailyData {
+    pub active_daily_coding_challenge_question: DailyChallenge,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DailyChallengesObj {
+    pub data: DailyChallengesData,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyChallengesData {
+    pub daily_coding_challenge_v2: DailyChallengeList,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DailyChallengeList {
+    #[serde(default, deserialize_with = "deserialize_vec_or_default")]
+    pub challenges: Vec<DailyChallenge>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DailyChallenge {
+    /// Challenge date in `YYYY-MM-DD` format (UTC).
+    pub date: String,
+    pub question: DailyQuestion,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyQuestion {
+    pub title_slug: String,
+}
+
+/// A calendar date in `YYYY-MM-DD` format, as used by LeetCode daily challenges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChallengeDate {
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl FromStr for ChallengeDate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid date `{}`, expected YYYY-MM-DD", s);
+
+        let mut parts = s.trim().splitn(3, '-');
+        let mut next_part = |len: usize| {
+            parts
+                .next()
+                .filter(|part| part.len() == len)
+                .and_then(|part| part.parse::<u32>().ok())
+                .ok_or_else(invalid)
+        };
+        let year = next_part(4)?;
+        let month = next_part(2)?;
+        let day = next_part(2)?;
+
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(invalid());
+        }
+
+        Ok(Self { year, month, day })
+    }
+}
+
+impl ChallengeDate {
+    /// Today's date in UTC, the day LeetCode's active daily challenge belongs to.
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Self::from_days_since_epoch(seconds / 86_400)
+    }
+
+    /// Converts a number of days since 1970-01-01 to a calendar date.
+    fn from_days_since_epoch(days: u64) -> Self {
+        // Civil calendar conversion over 400-year eras, starting the year in March
+        let days = days + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days % 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+        Self {
+            year: year as u32,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+}
+
+impl fmt::Display for ChallengeDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_challenge_date() {
+        let date = ChallengeDate::from_str("2024-05-01").unwrap();
+        assert_eq!(
+            date,
+            ChallengeDate {
+                year: 2024,
+                month: 5,
+                day: 1
+            }
+        );
+        assert_eq!(date.to_string(), "2024-05-01");
+    }
+
+    #[test]
+    fn test_challenge_date_from_days_since_epoch() {
+        assert_eq!(
+            ChallengeDate::from_days_since_epoch(0).to_string(),
+            "1970-01-01"
+        );
+        assert_eq!(
+            ChallengeDate::from_days_since_epoch(19_782).to_string(),
+            "2024-02-29"
+        );
+        assert_eq!(
+            ChallengeDate::from_days_since_epoch(20_453).to_string(),
+            "2025-12-31"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_challenge_date() {
+        assert!(ChallengeDate::from_str("2024-5-1").is_err());
+        assert!(ChallengeDate::from_str("2024-13-01").is_err());
+        assert!(ChallengeDate::from_str("2024-05").is_err());
+        assert!(ChallengeDate::from_str("today").is_err());
+    }
+}
@@ -1,41 +1,107 @@
+pub mod contest;
+pub mod daily;
+pub mod problemset;
 pub mod question;
 pub mod solution;
 pub mod submission;
+#[cfg(test)]
+pub(crate) mod test_support;
 
-use crate::config::{LeetCodeCookies, WebDriverConfig};
+use crate::auth::session::{remaining_validity, session_expiry};
+use crate::config::{
+    ExtractionStrategy, LeetCodeCookies, NetworkConfig, PremiumOnlyPolicy, RateLimit, RateLimits,
+    ScrapingConfig, WebDriverConfig,
+};
 use crate::markdown::{MarkdownCodeBlock, ToMarkdown};
+use crate::metrics;
+use contest::{ContestData, ContestObj};
+use daily::{ActiveDailyObj, ChallengeDate, DailyChallenge, DailyChallengesObj};
 use governor::{DefaultDirectRateLimiter, Jitter, Quota, RateLimiter};
-use question::{QuestionDetail, QuestionObj};
+use problemset::{ProblemsetObj, ProblemsetQuestion, QuestionListFilter};
+use question::{QuestionDetail, QuestionObj, QuestionSlugObj};
+use solution::{SolutionObj, resolve_editorial_urls};
 use submission::SubmissionMeta;
 
 use fantoccini::cookies::Cookie;
-use reqwest::header::{ACCEPT, CONTENT_TYPE, COOKIE, HeaderMap, HeaderName, ORIGIN, REFERER};
+use reqwest::Certificate;
+use reqwest::StatusCode;
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::{ACCEPT, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue, ORIGIN, REFERER};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use submission::SubmissionObj;
 use url::Url;
 
+use super::challenge::{is_challenge_page, is_challenge_response};
 use super::retry;
+use super::web_driver::{WebDriverSession, save_debug_artifacts};
 use crate::error::{CrawlerError, DocumentConversionError};
 use fantoccini::error::CmdError;
 pub use question::QuestionDescription;
 use std::fmt::Write;
 use std::num::NonZeroU32;
-use std::time::Duration;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
 use tokio::time::sleep;
-use tracing::{debug, info};
+use tracing::{debug, info, instrument, warn};
 
-pub const USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_11_6) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/54.0.2840.98 Safari/537.36";
-pub const LEET_CODE_HOST: &str = "https://leetcode.com";
-pub const LEET_CODE_API: &str = "https://leetcode.com/graphql";
-const LEET_CODE_COOKIE_DOMAIN: &str = "leetcode.com";
+/// User agent of the HTTP client unless `network.user_agent` is configured.
+pub const USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/141.0.0.0 Safari/537.36";
 const X_CSRF_TOKEN: HeaderName = HeaderName::from_static("x-csrftoken");
+const CHALLENGE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The LeetCode site an account belongs to. Accounts on leetcode.com and leetcode.cn are separate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LeetCodeSite {
+    #[default]
+    Com,
+    Cn,
+}
+
+impl LeetCodeSite {
+    pub fn host(self) -> &'static str {
+        match self {
+            LeetCodeSite::Com => "https://leetcode.com",
+            LeetCodeSite::Cn => "https://leetcode.cn",
+        }
+    }
+
+    pub fn api(self) -> &'static str {
+        match self {
+            LeetCodeSite::Com => "https://leetcode.com/graphql",
+            LeetCodeSite::Cn => "https://leetcode.cn/graphql/",
+        }
+    }
+
+    pub fn cookie_domain(self) -> &'static str {
+        match self {
+            LeetCodeSite::Com => "leetcode.com",
+            LeetCodeSite::Cn => "leetcode.cn",
+        }
+    }
+
+    fn host_url(self) -> Url {
+        Url::parse(self.host()).expect("LeetCode host is a valid URL")
+    }
 
-/// Represents a LeetCode problem with its name, description and accepted submissions.
+    fn api_url(self) -> Url {
+        Url::parse(self.api()).expect("LeetCode API is a valid URL")
+    }
+}
+
+/// Represents a LeetCode problem with its name, description, editorial and accepted submissions.
 #[derive(Debug)]
 pub struct LeetCodeProblem {
     pub name: String,
     pub description: QuestionDetail,
+    /// The official editorial in Markdown, if the problem has one visible to the current session.
+    pub editorial: Option<String>,
     pub submissions: Vec<MarkdownCodeBlock>,
+    /// Additional Anki tags for the problem, on top of its topic tags.
+    pub extra_tags: Vec<String>,
 }
 
 impl ToMarkdown for LeetCodeProblem {
@@ -50,6 +116,18 @@ impl ToMarkdown for LeetCodeProblem {
             self.description.to_markdown()?
         )?;
 
+        if !self.description.hints.is_empty() {
+            write!(markdown, "\n\n# Hints")?;
+            for (i, hint) in self.description.hints.iter().enumerate() {
+                write!(
+                    markdown,
+                    "\n\n{}. {}",
+                    i + 1,
+                    QuestionDescription::from(hint).to_markdown()?
+                )?;
+            }
+        }
+
         if !self.submissions.is_empty() {
             write!(markdown, "\n\n# Solution")?;
             for (i, submission) in self.submissions.iter().enumerate() {
@@ -62,67 +140,156 @@ impl ToMarkdown for LeetCodeProblem {
             }
         }
 
+        if let Some(editorial) = &self.editorial {
+            write!(markdown, "\n\n# Editorial\n\n{}", editorial.trim())?;
+        }
+
         Ok(markdown)
     }
 }
 
 /// A crawler for LeetCode problems. Some methods require a web driver to fetch dynamic content, while others use the LeetCode GraphQL API for static data retrieval.
 pub struct LeetCodeCrawler {
-    web_driver: fantoccini::Client,
+    web_driver: Option<WebDriverSession>,
+    /// Held while a page is loaded and read, since the problem detail, editorial and submissions
+    /// are crawled concurrently but share the one browser window.
+    web_driver_lock: Mutex<()>,
+    headless: bool,
+    scraping: ScrapingConfig,
+    premium_only: PremiumOnlyPolicy,
     client: reqwest::Client,
+    site: LeetCodeSite,
+    /// GraphQL endpoint, `site.api()` unless a test points it at a stand-in server.
+    api: Url,
+    /// Session cookies shared by the HTTP client and the web driver, updated as LeetCode rotates them.
+    cookie_jar: Option<Arc<Jar>>,
+    graphql_throttle: Throttle,
+    web_driver_throttle: Throttle,
+    anonymous: bool,
+}
+
+/// Spaces out one kind of request according to its rate limit, with a random delay on top.
+struct Throttle {
     rate_limiter: DefaultDirectRateLimiter,
     jitter: Jitter,
 }
 
+impl Throttle {
+    fn new(rate_limit: &RateLimit) -> Result<Self, CrawlerError> {
+        if !rate_limit.requests_per_second.is_finite() || rate_limit.requests_per_second <= 0.0 {
+            return Err(CrawlerError::InvalidRateLimit);
+        }
+        let burst = NonZeroU32::new(rate_limit.burst).ok_or(CrawlerError::InvalidRateLimit)?;
+        let quota = Quota::with_period(Duration::from_secs_f64(
+            1.0 / rate_limit.requests_per_second,
+        ))
+        .ok_or(CrawlerError::InvalidRateLimit)?
+        .allow_burst(burst);
+        let jitter_min = Duration::from_millis(rate_limit.jitter_min_ms);
+        let jitter_max = Duration::from_millis(rate_limit.jitter_max_ms);
+
+        Ok(Self {
+            rate_limiter: RateLimiter::direct(quota),
+            jitter: Jitter::new(jitter_min, jitter_max.saturating_sub(jitter_min)),
+        })
+    }
+
+    async fn wait(&self) {
+        self.rate_limiter.until_ready_with_jitter(self.jitter).await;
+    }
+}
+
 impl LeetCodeCrawler {
     /// Creates a new `LeetCodeCrawler` instance with the provided web driver endpoint, headless mode, and cookies.
+    /// The session is validated first, so an expired cookie fails before the browser is started.
     pub async fn new(
-        rate_limit: u32,
+        rate_limits: &RateLimits,
+        site: LeetCodeSite,
         web_driver_config: &WebDriverConfig,
+        scraping: &ScrapingConfig,
+        network: &NetworkConfig,
         cookie: &LeetCodeCookies,
     ) -> Result<Self, CrawlerError> {
-        // Set up reqwest client
-        let client = Self::new_reqwest_client(cookie)?;
+        let mut crawler = Self::without_web_driver(rate_limits, site, network, cookie)?;
+        crawler.headless = web_driver_config.headless;
+        crawler.scraping = scraping.clone();
+        check_session_expiry(cookie)?;
+        crawler.ensure_signed_in().await?;
         // Set up fantoccini web driver client
-        let web_driver = Self::new_web_driver_client(
-            &web_driver_config.endpoint,
-            web_driver_config.headless,
-            cookie,
-        )
-        .await?;
-        // Create a rate limiter for the crawler
-        let quota =
-            Quota::per_second(NonZeroU32::new(rate_limit).ok_or(CrawlerError::ZeroRateLimit)?);
-        let rate_limiter = RateLimiter::direct(quota);
-        let jitter = Jitter::new(Duration::from_millis(200), Duration::from_millis(500));
+        let web_driver =
+            Self::new_web_driver_client(web_driver_config, network, site, cookie).await?;
+        crawler.web_driver = Some(web_driver);
+
+        Ok(crawler)
+    }
+
+    /// Creates a new `LeetCodeCrawler` instance that only talks to the GraphQL API.
+    /// Fetching submitted code is not possible without a web driver.
+    pub fn without_web_driver(
+        rate_limits: &RateLimits,
+        site: LeetCodeSite,
+        network: &NetworkConfig,
+        cookie: &LeetCodeCookies,
+    ) -> Result<Self, CrawlerError> {
+        Self::with_reqwest_client(rate_limits, site, network, Some(cookie))
+    }
+
+    /// Creates a new `LeetCodeCrawler` instance without a session cookie.
+    /// Only public data (description, tags, hints and editorial) can be crawled; submissions are left empty.
+    pub fn anonymous(
+        rate_limits: &RateLimits,
+        site: LeetCodeSite,
+        network: &NetworkConfig,
+    ) -> Result<Self, CrawlerError> {
+        Self::with_reqwest_client(rate_limits, site, network, None)
+    }
+
+    fn with_reqwest_client(
+        rate_limits: &RateLimits,
+        site: LeetCodeSite,
+        network: &NetworkConfig,
+        cookie: Option<&LeetCodeCookies>,
+    ) -> Result<Self, CrawlerError> {
+        // Set up reqwest client, keeping the cookies LeetCode refreshes through `Set-Cookie`
+        let cookie_jar = cookie.map(|cookie| new_cookie_jar(site, cookie));
+        let client = Self::new_reqwest_client(site, network, cookie_jar.as_ref())?;
+        // API requests and page loads are limited separately
+        let graphql_throttle = Throttle::new(&rate_limits.graphql)?;
+        let web_driver_throttle = Throttle::new(&rate_limits.web_driver)?;
 
         Ok(Self {
-            web_driver,
+            web_driver: None,
+            web_driver_lock: Mutex::new(()),
+            headless: false,
+            scraping: ScrapingConfig::default(),
+            premium_only: PremiumOnlyPolicy::default(),
             client,
-            rate_limiter,
-            jitter,
+            site,
+            api: site.api_url(),
+            cookie_jar,
+            graphql_throttle,
+            web_driver_throttle,
+            anonymous: cookie.is_none(),
         })
     }
 
-    /// Creates a new reqwest client with the provided cookies and set headers(referer and user-agent).
-    fn new_reqwest_client(cookie: &LeetCodeCookies) -> Result<reqwest::Client, CrawlerError> {
+    /// Creates a new reqwest client with the provided cookie jar, network settings and set headers(referer and user-agent).
+    /// Without a cookie jar the client sends anonymous requests.
+    fn new_reqwest_client(
+        site: LeetCodeSite,
+        network: &NetworkConfig,
+        cookie_jar: Option<&Arc<Jar>>,
+    ) -> Result<reqwest::Client, CrawlerError> {
         let mut headers = HeaderMap::new();
         headers.insert(
             REFERER,
-            LEET_CODE_HOST
+            site.host()
                 .parse()
                 .map_err(|e| CrawlerError::Other(format!("referer parse error : {}", e)))?,
         );
-        headers.insert(
-            COOKIE,
-            cookie
-                .to_string()
-                .parse()
-                .map_err(|e| CrawlerError::Other(format!("cookie parse error: {}", e)))?,
-        );
         headers.insert(
             ORIGIN,
-            LEET_CODE_HOST
+            site.host()
                 .parse()
                 .map_err(|e| CrawlerError::Other(format!("origin parse error: {}", e)))?,
         );
@@ -132,44 +299,68 @@ impl LeetCodeCrawler {
                 .parse()
                 .map_err(|e| CrawlerError::Other(format!("accept parse error: {}", e)))?,
         );
-        headers.insert(
-            X_CSRF_TOKEN,
-            cookie
-                .csrf_token
-                .parse()
-                .map_err(|e| CrawlerError::Other(format!("x-csrftoken parse error: {}", e)))?,
-        );
-
-        let client = reqwest::Client::builder()
-            .user_agent(USER_AGENT)
-            .default_headers(headers)
+        for (name, value) in &network.headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+                CrawlerError::NetworkConfigError(format!("header name `{}`: {}", name, e))
+            })?;
+            let value = HeaderValue::from_str(value).map_err(|e| {
+                CrawlerError::NetworkConfigError(format!("value of header `{}`: {}", name, e))
+            })?;
+            headers.insert(name, value);
+        }
+        let mut client_builder = reqwest::Client::builder()
+            .user_agent(network.user_agent.as_deref().unwrap_or(USER_AGENT))
+            .default_headers(headers);
+        if let Some(proxy) = &network.proxy {
+            client_builder = client_builder.proxy(
+                reqwest::Proxy::all(proxy.as_str())
+                    .map_err(CrawlerError::BuildReqwestClientError)?,
+            );
+        }
+        for path in &network.ca_certificates {
+            let pem = std::fs::read(path).map_err(|e| {
+                CrawlerError::NetworkConfigError(format!(
+                    "failed to read CA certificate {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+            for certificate in
+                Certificate::from_pem_bundle(&pem).map_err(CrawlerError::BuildReqwestClientError)?
+            {
+                client_builder = client_builder.add_root_certificate(certificate);
+            }
+        }
+        if let Some(connect_timeout_secs) = network.connect_timeout_secs {
+            client_builder =
+                client_builder.connect_timeout(Duration::from_secs(connect_timeout_secs));
+        }
+        if let Some(timeout_secs) = network.timeout_secs {
+            client_builder = client_builder.timeout(Duration::from_secs(timeout_secs));
+        }
+        if let Some(cookie_jar) = cookie_jar {
+            client_builder = client_builder.cookie_provider(Arc::clone(cookie_jar));
+        }
+        let client = client_builder
             .build()
             .map_err(CrawlerError::BuildReqwestClientError)?;
 
         Ok(client)
     }
 
-    /// Creates a new fantoccini web driver client with the provided endpoint, headless mode, and cookies.
+    /// Creates a new fantoccini web driver client with the provided web driver settings and cookies.
     async fn new_web_driver_client(
-        web_driver_endpoint: &Url,
-        headless: bool,
+        web_driver_config: &WebDriverConfig,
+        network: &NetworkConfig,
+        site: LeetCodeSite,
         cookie: &LeetCodeCookies,
-    ) -> Result<fantoccini::Client, CrawlerError> {
-        let mut web_driver_builder = fantoccini::ClientBuilder::native();
-
-        if headless {
-            let mut caps = serde_json::map::Map::new();
-            let opts = json!({ "args": ["-headless"] });
-            caps.insert("moz:firefoxOptions".to_string(), opts);
-
-            web_driver_builder.capabilities(caps);
-        }
-
-        let web_driver = web_driver_builder
-            .connect(web_driver_endpoint.as_str())
-            .await?;
+    ) -> Result<WebDriverSession, CrawlerError> {
+        let web_driver =
+            WebDriverSession::connect(web_driver_config, network, web_driver_config.headless)
+                .await?;
+        let user_agent = network.user_agent.as_deref().unwrap_or(USER_AGENT);
 
-        if let Err(e) = set_up_web_driver(&web_driver, cookie).await {
+        if let Err(e) = set_up_web_driver(&web_driver, user_agent, site, cookie).await {
             web_driver.close().await?;
             return Err(e.into());
         }
@@ -178,12 +369,28 @@ impl LeetCodeCrawler {
     }
 
     /// Crawls a LeetCode problem by its slug and returns a `LeetCodeProblem` struct.
-    /// It fetches the problem description, solution, and accepted submissions based on the provided `CrawlConfig`.
+    /// It fetches the problem description, the editorial and all accepted submissions.
     pub async fn crawl_problem(&self, slug: &str) -> Result<LeetCodeProblem, CrawlerError> {
-        let (question_detail, submissions) = tokio::try_join!(
-            self.fetch_problem_detail(slug),
-            self.fetch_accepted_submissions(slug)
-        )?;
+        self.crawl_problem_matching(slug, |_| true).await
+    }
+
+    /// Crawls a LeetCode problem by its slug, keeping only the accepted submissions that satisfy `filter`.
+    #[instrument(name = "crawl_problem", skip_all, fields(slug = %slug))]
+    pub async fn crawl_problem_matching(
+        &self,
+        slug: &str,
+        filter: impl Fn(&SubmissionMeta) -> bool,
+    ) -> Result<LeetCodeProblem, CrawlerError> {
+        // Fetched first, so that unknown and skipped premium-only problems cost no further requests
+        let question_detail = self.fetch_problem_detail(slug).await?;
+        let submissions = async {
+            if self.anonymous {
+                return Ok(Vec::new());
+            }
+
+            self.fetch_accepted_submissions_matching(slug, filter).await
+        };
+        let (editorial, submissions) = tokio::try_join!(self.fetch_editorial(slug), submissions)?;
         let name = format!(
             "{}. {}",
             question_detail.question_frontend_id, question_detail.question_title
@@ -192,7 +399,9 @@ impl LeetCodeCrawler {
         let problem = LeetCodeProblem {
             name,
             description: question_detail,
+            editorial,
             submissions,
+            extra_tags: Vec::new(),
         };
 
         Ok(problem)
@@ -203,25 +412,238 @@ impl LeetCodeCrawler {
         info!("Fetching problem detail for slug: {}", slug);
 
         let question_obj: QuestionObj = self.post_graphql(
-            r#"query getQuestionDetail($titleSlug:String!){question(titleSlug:$titleSlug){questionFrontendId questionTitle questionTitleSlug content difficulty topicTags{name slug}}}"#,
+            r#"query getQuestionDetail($titleSlug:String!){question(titleSlug:$titleSlug){questionFrontendId questionTitle questionTitleSlug content difficulty isPaidOnly topicTags{name slug} hints}}"#,
             json!({ "titleSlug": slug }),
         ).await?;
+        let mut question = question_obj
+            .data
+            .question
+            .ok_or_else(|| CrawlerError::NotFound(format!("problem `{}`", slug)))?;
+        // LeetCode hides the description of premium-only problems from sessions without Premium
+        if question.content.is_empty() {
+            if !question.is_paid_only {
+                return Err(CrawlerError::UnexpectedResponse(format!(
+                    "problem `{}` has no description",
+                    slug
+                )));
+            }
+            match self.premium_only {
+                PremiumOnlyPolicy::Skip => return Err(CrawlerError::PremiumOnly(slug.to_string())),
+                PremiumOnlyPolicy::Stub => {
+                    warn!(
+                        "Problem {} is premium-only, exporting it without its description",
+                        slug
+                    );
+                    question.content = format!(
+                        "<p><em>This problem is only available with LeetCode Premium: <a href=\"{}/problems/{}/\">{}</a></em></p>",
+                        self.site.host(),
+                        slug,
+                        question.question_title
+                    );
+                }
+            }
+        }
+
+        Ok(question)
+    }
+
+    /// Checks whether a problem with the given slug exists.
+    pub async fn problem_exists(&self, slug: &str) -> Result<bool, CrawlerError> {
+        debug!("Checking whether problem {} exists", slug);
+
+        let question_obj: QuestionSlugObj = self
+            .post_graphql(
+                r#"query problemExists($titleSlug:String!){question(titleSlug:$titleSlug){titleSlug}}"#,
+                json!({ "titleSlug": slug }),
+            )
+            .await?;
+
+        Ok(question_obj.data.question.is_some())
+    }
+
+    /// Fetches the official editorial of a problem in Markdown.
+    /// Returns `None` when the problem has no editorial or it is not visible to the current session.
+    pub async fn fetch_editorial(&self, slug: &str) -> Result<Option<String>, CrawlerError> {
+        debug!("Fetching editorial for slug: {}", slug);
+
+        let solution_obj: SolutionObj = self.post_graphql(
+            r#"query officialSolution($titleSlug:String!){question(titleSlug:$titleSlug){solution{id content canSeeDetail paidOnly rating{count average}}}}"#,
+            json!({ "titleSlug": slug }),
+        )
+        .await?;
+
+        // An unknown slug is reported by the problem detail, which is fetched alongside
+        let editorial_url = self
+            .site
+            .host_url()
+            .join(&format!("problems/{}/solution/", slug))?;
+        let editorial = solution_obj
+            .data
+            .question
+            .and_then(|question| question.solution)
+            .as_ref()
+            .and_then(|solution| solution.visible_content())
+            .map(|content| resolve_editorial_urls(content, &editorial_url));
+
+        Ok(editorial)
+    }
+
+    /// Fetches every question of the problemset matching the given filter, following pagination.
+    pub async fn fetch_problemset_questions(
+        &self,
+        filter: &QuestionListFilter,
+    ) -> Result<Vec<ProblemsetQuestion>, CrawlerError> {
+        const PAGE_SIZE: usize = 100;
 
-        Ok(question_obj.data.question)
+        info!(
+            "Fetching problemset questions with filters: {}",
+            filter.to_variables()
+        );
+
+        let mut questions = Vec::new();
+        loop {
+            let problemset_obj: ProblemsetObj = self.post_graphql(
+                r#"query problemsetQuestionList($categorySlug:String $limit:Int $skip:Int $filters:QuestionListFilterInput){problemsetQuestionList:questionList(categorySlug:$categorySlug limit:$limit skip:$skip filters:$filters){total:totalNum questions:data{frontendQuestionId:questionFrontendId title titleSlug difficulty isPaidOnly acRate status topicTags{name slug}}}}"#,
+                json!({
+                    "categorySlug": "",
+                    "limit": PAGE_SIZE,
+                    "skip": questions.len(),
+                    "filters": filter.to_variables(),
+                }),
+            )
+            .await?;
+
+            let list = problemset_obj.data.problemset_question_list;
+            let page_len = list.questions.len();
+            questions.extend(list.questions);
+
+            if page_len == 0 || questions.len() >= list.total {
+                break;
+            }
+        }
+
+        info!("Found {} problemset questions", questions.len());
+
+        Ok(questions)
+    }
+
+    /// Fetches a contest by its slug (e.g. `weekly-contest-400`) together with its problem list.
+    pub async fn fetch_contest(&self, contest_slug: &str) -> Result<ContestData, CrawlerError> {
+        info!("Fetching contest: {}", contest_slug);
+
+        let contest_obj: ContestObj = self.post_graphql(
+            r#"query contestInfo($contestSlug:String!){contest(titleSlug:$contestSlug){title titleSlug startTime duration} contestQuestionList(contestSlug:$contestSlug){title titleSlug}}"#,
+            json!({ "contestSlug": contest_slug }),
+        )
+        .await?;
+
+        Ok(contest_obj.data)
+    }
+
+    /// Fetches the daily coding challenge of the given date, or today's challenge when no date is given.
+    pub async fn fetch_daily_challenge(
+        &self,
+        date: Option<ChallengeDate>,
+    ) -> Result<DailyChallenge, CrawlerError> {
+        let Some(date) = date else {
+            info!("Fetching today's daily challenge");
+            let daily_obj: ActiveDailyObj = self
+                .post_graphql(
+                    r#"query questionOfToday{activeDailyCodingChallengeQuestion{date question{titleSlug}}}"#,
+                    json!({}),
+                )
+                .await?;
+
+            return Ok(daily_obj.data.active_daily_coding_challenge_question);
+        };
+
+        info!("Fetching daily challenge for date: {}", date);
+        let daily_obj: DailyChallengesObj = self.post_graphql(
+            r#"query dailyCodingQuestionRecords($year:Int! $month:Int!){dailyCodingChallengeV2(year:$year month:$month){challenges{date question{titleSlug}}}}"#,
+            json!({ "year": date.year, "month": date.month }),
+        )
+        .await?;
+
+        let date = date.to_string();
+        daily_obj
+            .data
+            .daily_coding_challenge_v2
+            .challenges
+            .into_iter()
+            .find(|challenge| challenge.date == date)
+            .ok_or_else(|| CrawlerError::NotFound(format!("daily challenge of {}", date)))
+    }
+
+    /// Looks up the slug of the problem a submission belongs to.
+    pub async fn fetch_submission_question_slug(
+        &self,
+        submission_id: u64,
+    ) -> Result<String, CrawlerError> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SubmissionDetailsResponse {
+            data: SubmissionDetailsData,
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SubmissionDetailsData {
+            submission_details: Option<SubmissionDetails>,
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SubmissionDetails {
+            question: SubmissionQuestion,
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SubmissionQuestion {
+            title_slug: String,
+        }
+
+        info!("Resolving problem of submission: {}", submission_id);
+
+        let response: SubmissionDetailsResponse = self
+            .post_graphql(
+                r#"query submissionDetails($submissionId:Int!){submissionDetails(submissionId:$submissionId){question{titleSlug}}}"#,
+                json!({ "submissionId": submission_id }),
+            )
+            .await?;
+
+        response
+            .data
+            .submission_details
+            .map(|details| details.question.title_slug)
+            .ok_or_else(|| CrawlerError::NotFound(format!("submission {}", submission_id)))
     }
 
     /// Fetches all accepted submissions for a given problem slug and returns a vec of `MarkdownCodeBlock`.
     pub async fn fetch_accepted_submissions(
         &self,
         slug: &str,
+    ) -> Result<Vec<MarkdownCodeBlock>, CrawlerError> {
+        self.fetch_accepted_submissions_matching(slug, |_| true)
+            .await
+    }
+
+    /// Fetches the accepted submissions for a given problem slug that satisfy `filter`.
+    /// The session is not checked again here, `LeetCodeCrawler::new` already did.
+    pub async fn fetch_accepted_submissions_matching(
+        &self,
+        slug: &str,
+        filter: impl Fn(&SubmissionMeta) -> bool,
     ) -> Result<Vec<MarkdownCodeBlock>, CrawlerError> {
         info!("Fetching accepted submissions for slug: {}", slug);
 
-        self.ensure_signed_in().await?;
         let submission_metas = self.fetch_submission_metas(slug).await?;
         let mut code_blocks = Vec::with_capacity(submission_metas.len());
 
-        for meta in submission_metas.iter().filter(|meta| meta.is_accepted()) {
+        for meta in submission_metas
+            .iter()
+            .filter(|meta| meta.is_accepted() && filter(meta))
+        {
             let code_block = self.fetch_submission(meta).await?;
             code_blocks.push(code_block);
         }
@@ -235,7 +657,8 @@ impl LeetCodeCrawler {
         Ok(code_blocks)
     }
 
-    async fn ensure_signed_in(&self) -> Result<(), CrawlerError> {
+    /// Checks that the configured cookies belong to a signed-in session and returns the username.
+    pub async fn ensure_signed_in(&self) -> Result<String, CrawlerError> {
         #[derive(serde::Deserialize)]
         #[serde(rename_all = "camelCase")]
         struct UserStatusResponse {
@@ -263,8 +686,8 @@ impl LeetCodeCrawler {
             .await?;
 
         if !response.data.user_status.is_signed_in {
-            return Err(CrawlerError::Other(
-                "LeetCode did not recognize the configured cookies as a signed-in session. Refresh the full browser Cookie header from a session that can open your submissions page."
+            return Err(CrawlerError::InvalidSession(
+                "LeetCode did not recognize the configured cookies as a signed-in session"
                     .to_string(),
             ));
         }
@@ -274,22 +697,43 @@ impl LeetCodeCrawler {
             response.data.user_status.username
         );
 
-        Ok(())
+        Ok(response.data.user_status.username)
     }
 
-    /// Fetches the submission metadata for a given problem slug, it returns a vector of `SubmissionMeta`.
+    /// Fetches the submission metadata for a given problem slug, following pagination, and returns
+    /// a vector of `SubmissionMeta`.
     async fn fetch_submission_metas(
         &self,
         slug: &str,
     ) -> Result<Vec<SubmissionMeta>, CrawlerError> {
+        const PAGE_SIZE: usize = 20;
+
         debug!("Fetching submission metadata for slug: {}", slug);
-        let submission_obj: SubmissionObj = self.post_graphql(
-            r#"query Submissions($offset:Int! $limit:Int! $lastKey:String $questionSlug:String!){submissionList(offset:$offset limit:$limit lastKey:$lastKey questionSlug:$questionSlug){submissions{statusDisplay lang url}}}"#,
-            json!({ "offset": 0, "limit": 20, "lastKey": "", "questionSlug": slug }),
-        )
-        .await?;
+        let mut submissions = Vec::new();
+        let mut last_key = String::new();
+        loop {
+            let submission_obj: SubmissionObj = self.post_graphql(
+                r#"query Submissions($offset:Int! $limit:Int! $lastKey:String $questionSlug:String!){submissionList(offset:$offset limit:$limit lastKey:$lastKey questionSlug:$questionSlug){lastKey hasNext submissions{statusDisplay lang url timestamp}}}"#,
+                json!({
+                    "offset": submissions.len(),
+                    "limit": PAGE_SIZE,
+                    "lastKey": last_key,
+                    "questionSlug": slug,
+                }),
+            )
+            .await?;
+
+            let list = submission_obj.data.submission_list;
+            let page_len = list.submissions.len();
+            submissions.extend(list.submissions);
 
-        Ok(submission_obj.data.submission_list.submissions)
+            match list.last_key {
+                Some(next_key) if list.has_next && page_len > 0 => last_key = next_key,
+                _ => break,
+            }
+        }
+
+        Ok(submissions)
     }
 
     /// Fetches the submitted code for a given submission metadata. It returns a `MarkdownCodeBlock` containing the language and code.
@@ -297,13 +741,13 @@ impl LeetCodeCrawler {
         &self,
         submission_meta: &SubmissionMeta,
     ) -> Result<MarkdownCodeBlock, CrawlerError> {
-        let host = Url::parse(LEET_CODE_HOST)?;
-        let url = host.join(&submission_meta.url)?;
+        let url = self.site.host_url().join(&submission_meta.url)?;
 
         let code_text = retry(3, || async {
             self.fetch_submitted_code(url.as_str()).await
         })
         .await?;
+        metrics::count(metrics::SUBMISSIONS_FETCHED);
 
         let code_block = MarkdownCodeBlock {
             language: submission_meta.lang.clone(),
@@ -314,76 +758,102 @@ impl LeetCodeCrawler {
     }
 
     /// Fetches the submitted code for a given submission URL.
+    #[instrument(name = "fetch_submitted_code", level = "debug", skip_all)]
     async fn fetch_submitted_code(&self, url: &str) -> Result<String, CrawlerError> {
-        self.rate_limiter.until_ready_with_jitter(self.jitter).await;
-        debug!("Fetching submitted code from URL: {}", url);
-        self.web_driver.goto(url).await?;
+        let web_driver = self
+            .web_driver
+            .as_ref()
+            .ok_or(CrawlerError::WebDriverUnavailable)?;
 
-        for _ in 0..20 {
-            if let Some(code_text) = self.extract_submission_code().await? {
+        self.web_driver_throttle.wait().await;
+        let _web_driver_guard = self.web_driver_lock.lock().await;
+        debug!("Fetching submitted code from URL: {}", url);
+        self.push_cookies_to_web_driver(web_driver).await?;
+        web_driver.goto(url).await?;
+        self.wait_for_challenge(web_driver).await?;
+
+        for _ in 0..self.scraping.poll_attempts.max(1) {
+            if let Some(code_text) =
+                extract_submission_code(web_driver, &self.scraping.strategies).await?
+            {
+                self.pull_cookies_from_web_driver(web_driver).await?;
                 return Ok(code_text);
             }
 
-            sleep(Duration::from_millis(500)).await;
+            sleep(Duration::from_millis(self.scraping.poll_interval_ms)).await;
         }
 
-        Err(CrawlerError::EmptyResult("submission code in DOM"))
-    }
-
-    async fn extract_submission_code(&self) -> Result<Option<String>, CrawlerError> {
-        if let Ok(code_data) = self
-            .web_driver
-            .execute(
-                "return typeof pageData !== 'undefined' ? pageData.submissionCode : null;",
-                vec![],
-            )
-            .await
-            && let Some(code_text) = code_data
-                .as_str()
-                .map(normalize_submission_code)
-                .filter(|text| !text.is_empty())
-        {
-            return Ok(Some(code_text));
-        }
-
-        let dom_script = r#"
-            const code = document.querySelector('pre code');
-            if (!code) {
-              return null;
+        if let Some(debug_artifacts_dir) = &self.scraping.debug_artifacts_dir {
+            let name = format!(
+                "{}-{}",
+                Url::parse(url)
+                    .ok()
+                    .as_ref()
+                    .and_then(extract_submission_id_from_url)
+                    .map_or_else(|| "submission".to_string(), |id| id.to_string()),
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis()
+            );
+            match save_debug_artifacts(web_driver, debug_artifacts_dir, &name).await {
+                Ok(path) => warn!(
+                    "No submission code found, debug artifacts saved to {}",
+                    path.display()
+                ),
+                Err(e) => warn!(
+                    "No submission code found, and saving debug artifacts failed: {}",
+                    e
+                ),
             }
+        }
 
-            return (code.innerText || code.textContent || '').trim();
-        "#;
-        let code_data = self.web_driver.execute(dom_script, vec![]).await?;
-        let code_text = code_data
-            .as_str()
-            .map(normalize_submission_code)
-            .filter(|text| !text.is_empty());
-
-        Ok(code_text)
+        Err(CrawlerError::EmptyResult("submission code in DOM"))
     }
 
     /// Sends a GraphQL POST request to the LeetCode API with the provided query and variables.
+    #[instrument(name = "post_graphql", level = "debug", skip_all)]
     async fn post_graphql<T: serde::de::DeserializeOwned>(
         &self,
         query: &str,
         variables: serde_json::Value,
     ) -> Result<T, CrawlerError> {
-        self.rate_limiter.until_ready_with_jitter(self.jitter).await;
+        self.graphql_throttle.wait().await;
 
         let parameters = json!({
             "query": query,
             "variables": variables,
         });
 
-        debug!("Sending GraphQL request: {}", parameters);
+        // Variables may hold user data, so only the operation is logged
+        debug!("Sending GraphQL request: {}", operation_name(query));
+
+        let (mut status, mut content_type, mut body) = self.send_graphql(&parameters).await?;
+        if is_challenge_response(&content_type, &body) {
+            // The browser can pass the challenge; its clearance cookie then works for the API too
+            self.solve_challenge_in_browser().await?;
+            (status, content_type, body) = self.send_graphql(&parameters).await?;
+            if is_challenge_response(&content_type, &body) {
+                return Err(CrawlerError::ChallengeRequired(
+                    "GraphQL API, even after the browser passed it".to_string(),
+                ));
+            }
+        }
+
+        parse_graphql_response(status, &content_type, &body)
+    }
 
-        let response = self
-            .client
-            .post(LEET_CODE_API)
-            .json(&parameters)
-            .send()
-            .await?;
+    /// Sends a GraphQL request and returns the status, content type and body of the response.
+    async fn send_graphql(
+        &self,
+        parameters: &serde_json::Value,
+    ) -> Result<(StatusCode, String, Vec<u8>), CrawlerError> {
+        let mut request = self.client.post(self.api.clone()).json(parameters);
+        // The CSRF header must match the current `csrftoken` cookie, which LeetCode may have rotated
+        if let Some(cookie) = self.current_cookies() {
+            request = request.header(X_CSRF_TOKEN, cookie.csrf_token);
+        }
+        let response = request.send().await?;
         let status = response.status();
         let content_type = response
             .headers()
@@ -391,50 +861,187 @@ impl LeetCodeCrawler {
             .and_then(|value| value.to_str().ok())
             .unwrap_or("unknown")
             .to_string();
-        let body = response.bytes().await?;
-
-        if !status.is_success() {
-            return Err(CrawlerError::Other(format!(
-                "LeetCode GraphQL returned HTTP {} (content-type: {}). Body starts with: {}",
-                status,
-                content_type,
-                body_excerpt(&body)
-            )));
-        }
-
-        let value: serde_json::Value = serde_json::from_slice(&body).map_err(|error| {
-            CrawlerError::Other(format!(
-                "Failed to decode LeetCode GraphQL response as JSON (content-type: {}): {}. Body starts with: {}",
-                content_type,
-                error,
-                body_excerpt(&body)
-            ))
-        })?;
+        let body = response.bytes().await?.to_vec();
+
+        Ok((status, content_type, body))
+    }
+
+    /// Opens LeetCode in the browser so that a challenge served to the HTTP client can be passed
+    /// there, then copies the resulting cookies to the HTTP client.
+    async fn solve_challenge_in_browser(&self) -> Result<(), CrawlerError> {
+        let web_driver = match &self.web_driver {
+            Some(web_driver) if !self.headless => web_driver,
+            _ => return Err(CrawlerError::ChallengeRequired("GraphQL API".to_string())),
+        };
 
-        if let Some(errors) = value.get("errors") {
-            return Err(CrawlerError::Other(format!(
-                "LeetCode GraphQL returned errors: {}",
-                errors
-            )));
+        let _web_driver_guard = self.web_driver_lock.lock().await;
+        self.push_cookies_to_web_driver(web_driver).await?;
+        web_driver.goto(self.site.host()).await?;
+        self.wait_for_challenge(web_driver).await?;
+        self.pull_cookies_from_web_driver(web_driver).await
+    }
+
+    /// Checks whether the browser shows a challenge page. In headless mode this fails right away,
+    /// otherwise it waits for the user to solve the challenge in the browser window.
+    async fn wait_for_challenge(
+        &self,
+        web_driver: &fantoccini::Client,
+    ) -> Result<(), CrawlerError> {
+        if !is_challenge_page(&web_driver.source().await?) {
+            return Ok(());
         }
 
-        let resp = serde_json::from_value(value).map_err(|error| {
-            CrawlerError::Other(format!(
-                "LeetCode GraphQL response schema did not match expected shape: {}",
-                error
-            ))
-        })?;
+        let url = web_driver.current_url().await?.to_string();
+        if self.headless {
+            return Err(CrawlerError::ChallengeRequired(url));
+        }
+
+        let timeout = Duration::from_secs(self.scraping.challenge_timeout_secs);
+        warn!(
+            "LeetCode shows a Cloudflare or captcha challenge at {}, solve it in the browser window to continue (waiting up to {} seconds)",
+            url,
+            timeout.as_secs()
+        );
+        let started = Instant::now();
+        while started.elapsed() < timeout {
+            sleep(CHALLENGE_POLL_INTERVAL).await;
+            if !is_challenge_page(&web_driver.source().await?) {
+                info!("Challenge solved, continuing");
+                return Ok(());
+            }
+        }
+
+        Err(CrawlerError::ChallengeRequired(url))
+    }
+
+    /// Sets what to do with premium-only problems whose description the session may not see.
+    pub fn set_premium_only_policy(&mut self, policy: PremiumOnlyPolicy) {
+        self.premium_only = policy;
+    }
+
+    /// Returns the session cookies as currently known, including the values refreshed by LeetCode
+    /// since the crawler was created. Returns `None` for anonymous crawlers.
+    pub fn current_cookies(&self) -> Option<LeetCodeCookies> {
+        jar_cookies(self.site, self.cookie_jar.as_deref()?)
+    }
+
+    /// Copies the session cookies that changed since the last page load into the web driver.
+    async fn push_cookies_to_web_driver(
+        &self,
+        web_driver: &fantoccini::Client,
+    ) -> Result<(), CrawlerError> {
+        let Some(cookie) = self.current_cookies() else {
+            return Ok(());
+        };
+
+        let browser_cookies = web_driver.get_all_cookies().await?;
+        for (name, value) in cookie_pairs(&cookie.raw) {
+            let up_to_date = browser_cookies.iter().any(|browser_cookie| {
+                browser_cookie.name() == name && browser_cookie.value() == value
+            });
+            if !up_to_date {
+                debug!("Updating cookie {} in the web driver", name);
+                web_driver
+                    .add_cookie(build_leetcode_cookie(self.site, name, value))
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
 
-        Ok(resp)
+    /// Copies the LeetCode cookies of the web driver, which may have been refreshed by the last page
+    /// load, into the cookie jar used by the HTTP client.
+    async fn pull_cookies_from_web_driver(
+        &self,
+        web_driver: &fantoccini::Client,
+    ) -> Result<(), CrawlerError> {
+        let Some(cookie_jar) = &self.cookie_jar else {
+            return Ok(());
+        };
+
+        for cookie in web_driver.get_all_cookies().await? {
+            let is_leetcode_cookie = cookie
+                .domain()
+                .is_none_or(|domain| domain.trim_start_matches('.') == self.site.cookie_domain());
+            if is_leetcode_cookie && !cookie.value().is_empty() {
+                add_jar_cookie(self.site, cookie_jar, cookie.name(), cookie.value());
+            }
+        }
+
+        Ok(())
     }
 
     /// Closes the web driver client session.
     pub async fn close(self) -> Result<(), CrawlerError> {
-        self.web_driver.close().await?;
+        if let Some(web_driver) = self.web_driver {
+            web_driver.close().await?;
+        }
         Ok(())
     }
 }
 
+/// A user-supplied reference to a LeetCode problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProblemQuery {
+    /// A problem slug taken from a problem URL.
+    Slug(String),
+    /// Text that may be a slug or a title, such as `subsets`. It is tried as a slug first.
+    SlugOrTitle(String),
+    /// A frontend question id (the number shown on the website).
+    FrontendId(String),
+    /// The id of a submission, taken from a `/submissions/detail/<id>` URL.
+    SubmissionId(u64),
+    /// Free text to match against problem titles.
+    Title(String),
+}
+
+impl FromStr for ProblemQuery {
+    type Err = CrawlerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let query = s.trim();
+        if query.is_empty() {
+            return Err(CrawlerError::SlugParseError(s.to_string()));
+        }
+
+        if let Ok(url) = Url::parse(query)
+            && matches!(url.scheme(), "http" | "https")
+        {
+            if let Some(submission_id) = extract_submission_id_from_url(&url) {
+                return Ok(Self::SubmissionId(submission_id));
+            }
+
+            return Ok(Self::Slug(extract_slug_from_url(&url)?.to_string()));
+        }
+
+        if query.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(Self::FrontendId(query.to_string()));
+        }
+
+        if query
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            return Ok(Self::SlugOrTitle(query.to_string()));
+        }
+
+        Ok(Self::Title(query.to_string()))
+    }
+}
+
+/// Extracts the submission id from a `/submissions/detail/<id>` URL.
+pub fn extract_submission_id_from_url(url: &Url) -> Option<u64> {
+    let mut path_segments = url.path_segments()?;
+
+    path_segments.find(|&s| s == "submissions")?;
+    if path_segments.next()? != "detail" {
+        return None;
+    }
+
+    path_segments.next()?.parse().ok()
+}
+
 /// Extracts the problem slug from a given LeetCode URL.
 /// The slug should be the first path segment after `/problems/`.
 pub fn extract_slug_from_url(url: &Url) -> Result<&str, CrawlerError> {
@@ -452,28 +1059,115 @@ pub fn extract_slug_from_url(url: &Url) -> Result<&str, CrawlerError> {
     Ok(slug)
 }
 
+/// Tries each extraction strategy in order and returns the first non-empty code.
+/// A strategy whose script throws is skipped, as the page may simply not be ready yet.
+async fn extract_submission_code(
+    web_driver: &fantoccini::Client,
+    strategies: &[ExtractionStrategy],
+) -> Result<Option<String>, CrawlerError> {
+    const SELECTOR_SCRIPT: &str = r#"
+        const code = document.querySelector(arguments[0]);
+        if (!code) {
+          return null;
+        }
+
+        return (code.innerText || code.textContent || '').trim();
+    "#;
+
+    for strategy in strategies {
+        let result = match strategy {
+            ExtractionStrategy::Script { script } => web_driver.execute(script, vec![]).await,
+            ExtractionStrategy::Selector { selector } => {
+                web_driver
+                    .execute(SELECTOR_SCRIPT, vec![json!(selector)])
+                    .await
+            }
+        };
+        let code_data = match result {
+            Ok(code_data) => code_data,
+            Err(e) if e.is_javascript_error() => {
+                debug!("Extraction strategy {:?} failed: {}", strategy, e);
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        if let Some(code_text) = code_data
+            .as_str()
+            .map(normalize_submission_code)
+            .filter(|text| !text.is_empty())
+        {
+            return Ok(Some(code_text));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Fails with `CrawlerError::InvalidSession` if the `LEETCODE_SESSION` token has already expired.
+/// Tokens whose expiry cannot be decoded are left for the server to judge.
+pub fn check_session_expiry(cookie: &LeetCodeCookies) -> Result<(), CrawlerError> {
+    match session_expiry(&cookie.leet_code_token) {
+        Some(expiry) if remaining_validity(expiry).is_none() => Err(CrawlerError::InvalidSession(
+            "the LEETCODE_SESSION cookie has expired".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
 /// Sets up the web driver with the necessary cookies(csrftoken and LEETCODE_SESSION) and user agent.
 async fn set_up_web_driver(
     web_driver: &fantoccini::Client,
+    user_agent: &str,
+    site: LeetCodeSite,
     cookie: &LeetCodeCookies,
 ) -> Result<(), CmdError> {
     // Set up user agent
-    web_driver.set_ua(USER_AGENT).await?;
+    web_driver.set_ua(user_agent).await?;
 
     // Set up cookies
-    web_driver.goto(LEET_CODE_HOST).await?;
+    web_driver.goto(site.host()).await?;
     for (name, value) in cookie_pairs(&cookie.raw) {
         web_driver
-            .add_cookie(build_leetcode_cookie(name, value))
+            .add_cookie(build_leetcode_cookie(site, name, value))
             .await?;
     }
 
     Ok(())
 }
 
-fn build_leetcode_cookie(name: &str, value: &str) -> Cookie<'static> {
+/// Creates a cookie jar holding every cookie of the raw `Cookie` header, scoped to LeetCode.
+fn new_cookie_jar(site: LeetCodeSite, cookie: &LeetCodeCookies) -> Arc<Jar> {
+    let cookie_jar = Arc::new(Jar::default());
+    for (name, value) in cookie_pairs(&cookie.raw) {
+        add_jar_cookie(site, &cookie_jar, name, value);
+    }
+
+    cookie_jar
+}
+
+fn add_jar_cookie(site: LeetCodeSite, cookie_jar: &Jar, name: &str, value: &str) {
+    cookie_jar.add_cookie_str(
+        &format!(
+            "{}={}; Domain={}; Path=/",
+            name,
+            value,
+            site.cookie_domain()
+        ),
+        &site.host_url(),
+    );
+}
+
+/// Reads the cookies the jar would send to LeetCode, if they still form a valid session cookie.
+fn jar_cookies(site: LeetCodeSite, cookie_jar: &Jar) -> Option<LeetCodeCookies> {
+    let header = cookie_jar.cookies(&site.host_url())?;
+
+    LeetCodeCookies::from_str(header.to_str().ok()?).ok()
+}
+
+fn build_leetcode_cookie(site: LeetCodeSite, name: &str, value: &str) -> Cookie<'static> {
     let mut cookie = Cookie::new(name.to_string(), value.to_string());
-    cookie.set_domain(LEET_CODE_COOKIE_DOMAIN);
+    cookie.set_domain(site.cookie_domain());
     cookie.set_path("/");
     // fantoccini 0.21 serializes an unset SameSite as `SameSite=None`, so these
     // cookies must be marked Secure to satisfy modern browser validation.
@@ -481,6 +1175,74 @@ fn build_leetcode_cookie(name: &str, value: &str) -> Cookie<'static> {
     cookie
 }
 
+/// Turns a GraphQL response into the expected data, or the matching `CrawlerError` variant.
+fn parse_graphql_response<T: serde::de::DeserializeOwned>(
+    status: StatusCode,
+    content_type: &str,
+    body: &[u8],
+) -> Result<T, CrawlerError> {
+    match status {
+        StatusCode::TOO_MANY_REQUESTS => return Err(CrawlerError::RateLimited),
+        StatusCode::UNAUTHORIZED => {
+            return Err(CrawlerError::InvalidSession(
+                "LeetCode answered with HTTP 401 Unauthorized".to_string(),
+            ));
+        }
+        status if !status.is_success() => {
+            return Err(CrawlerError::HttpStatus {
+                status: status.as_u16(),
+                content_type: content_type.to_string(),
+                excerpt: body_excerpt(body),
+            });
+        }
+        _ => {}
+    }
+
+    let mut value: serde_json::Value = serde_json::from_slice(body).map_err(|error| {
+        CrawlerError::UnexpectedResponse(format!(
+            "failed to decode GraphQL response as JSON (content-type: {}): {}. Body starts with: {}",
+            content_type,
+            error,
+            body_excerpt(body)
+        ))
+    })?;
+
+    if let Some(errors) = value.get_mut("errors").map(serde_json::Value::take) {
+        let errors = serde_json::from_value(errors).map_err(|error| {
+            CrawlerError::UnexpectedResponse(format!("malformed GraphQL errors: {}", error))
+        })?;
+        return Err(CrawlerError::GraphQlErrors(errors));
+    }
+
+    serde_json::from_value(value).map_err(|error| {
+        CrawlerError::UnexpectedResponse(format!(
+            "GraphQL response schema did not match expected shape: {}",
+            error
+        ))
+    })
+}
+
+/// Deserializes a list that LeetCode sends as `null` when it is empty.
+pub(crate) fn deserialize_vec_or_default<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::<Vec<T>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Returns the operation name of a GraphQL query, e.g. `getQuestionDetail`.
+fn operation_name(query: &str) -> &str {
+    query
+        .trim_start()
+        .trim_start_matches("query")
+        .trim_start()
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or("anonymous query")
+}
+
 fn body_excerpt(body: &[u8]) -> String {
     const MAX_LEN: usize = 240;
 
@@ -523,6 +1285,8 @@ fn normalize_submission_code(rendered: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_support::{mock_graphql, received_operations, test_crawler};
+    use wiremock::MockServer;
 
     #[test]
     fn test_extract_slug_from_url() {
@@ -546,17 +1310,84 @@ mod tests {
         assert!(extract_slug_from_url(&url).is_err());
     }
 
+    #[test]
+    fn test_extract_slug_from_contest_url() {
+        let url = Url::parse("https://leetcode.com/contest/weekly-contest-400/problems/two-sum/")
+            .unwrap();
+        assert_eq!(extract_slug_from_url(&url).unwrap(), "two-sum");
+    }
+
+    #[test]
+    fn test_extract_submission_id_from_url() {
+        let url = Url::parse("https://leetcode.com/submissions/detail/1234567/").unwrap();
+        assert_eq!(extract_submission_id_from_url(&url), Some(1234567));
+
+        let url = Url::parse("https://leetcode.com/problems/two-sum/submissions/1234567/").unwrap();
+        assert_eq!(extract_submission_id_from_url(&url), None);
+    }
+
+    #[test]
+    fn test_parse_problem_query() {
+        assert_eq!(
+            ProblemQuery::from_str("https://leetcode.com/problems/two-sum/").unwrap(),
+            ProblemQuery::Slug("two-sum".to_string())
+        );
+        assert_eq!(
+            ProblemQuery::from_str("https://leetcode.com/submissions/detail/42/").unwrap(),
+            ProblemQuery::SubmissionId(42)
+        );
+        assert_eq!(
+            ProblemQuery::from_str("1").unwrap(),
+            ProblemQuery::FrontendId("1".to_string())
+        );
+        assert_eq!(
+            ProblemQuery::from_str("two-sum").unwrap(),
+            ProblemQuery::SlugOrTitle("two-sum".to_string())
+        );
+        assert_eq!(
+            ProblemQuery::from_str("subsets").unwrap(),
+            ProblemQuery::SlugOrTitle("subsets".to_string())
+        );
+        assert_eq!(
+            ProblemQuery::from_str("two sum").unwrap(),
+            ProblemQuery::Title("two sum".to_string())
+        );
+        assert!(ProblemQuery::from_str("  ").is_err());
+    }
+
     #[test]
     fn test_build_leetcode_cookie_sets_required_attributes() {
-        let cookie = build_leetcode_cookie("csrftoken", "abc123");
+        let cookie = build_leetcode_cookie(LeetCodeSite::Com, "csrftoken", "abc123");
 
         assert_eq!(cookie.name(), "csrftoken");
         assert_eq!(cookie.value(), "abc123");
-        assert_eq!(cookie.domain(), Some(LEET_CODE_COOKIE_DOMAIN));
+        assert_eq!(cookie.domain(), Some("leetcode.com"));
         assert_eq!(cookie.path(), Some("/"));
         assert_eq!(cookie.secure(), Some(true));
     }
 
+    #[test]
+    fn test_cookie_jar_keeps_refreshed_cookies() {
+        let cookie = LeetCodeCookies::from_str(
+            "cf_clearance=keepme; csrftoken=old; LEETCODE_SESSION=session",
+        )
+        .unwrap();
+        let cookie_jar = new_cookie_jar(LeetCodeSite::Com, &cookie);
+
+        let set_cookie = reqwest::header::HeaderValue::from_static(
+            "csrftoken=new; Domain=.leetcode.com; Path=/; Secure",
+        );
+        cookie_jar.set_cookies(
+            &mut std::iter::once(&set_cookie),
+            &LeetCodeSite::Com.host_url(),
+        );
+
+        let refreshed = jar_cookies(LeetCodeSite::Com, &cookie_jar).unwrap();
+        assert_eq!(refreshed.csrf_token, "new");
+        assert_eq!(refreshed.leet_code_token, "session");
+        assert!(refreshed.raw.contains("cf_clearance=keepme"));
+    }
+
     #[test]
     fn test_cookie_pairs_preserve_values_with_equals_signs() {
         let pairs = cookie_pairs("foo=bar=baz; csrftoken=abc123; LEETCODE_SESSION=xyz789==");
@@ -588,4 +1419,269 @@ mod tests {
 
         assert_eq!(normalize_submission_code(expected), expected);
     }
+
+    #[test]
+    fn test_parse_graphql_response_errors() {
+        let error = parse_graphql_response::<serde_json::Value>(
+            StatusCode::OK,
+            "application/json",
+            br#"{"data":null,"errors":[{"message":"Invalid slug","path":["question",0,"content"]}]}"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "LeetCode GraphQL returned errors: Invalid slug (at question.0.content)"
+        );
+
+        assert!(matches!(
+            parse_graphql_response::<serde_json::Value>(
+                StatusCode::TOO_MANY_REQUESTS,
+                "text/plain",
+                b"slow down"
+            ),
+            Err(CrawlerError::RateLimited)
+        ));
+        assert!(matches!(
+            parse_graphql_response::<serde_json::Value>(
+                StatusCode::BAD_GATEWAY,
+                "text/html",
+                b"<html>Bad gateway</html>"
+            ),
+            Err(CrawlerError::HttpStatus { status: 502, .. })
+        ));
+        assert!(matches!(
+            parse_graphql_response::<QuestionObj>(
+                StatusCode::OK,
+                "application/json",
+                br#"{"data":{"question":{"questionTitle":1}}}"#
+            ),
+            Err(CrawlerError::UnexpectedResponse(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_problemset_questions() {
+        let server = MockServer::start().await;
+        mock_graphql(
+            &server,
+            "problemsetQuestionList",
+            json!({ "skip": 0, "filters": { "tags": ["array"] } }),
+            serde_json::from_str(include_str!(
+                "../../../tests/fixtures/leetcode/question_list.json"
+            ))
+            .unwrap(),
+        )
+        .await;
+        let crawler = test_crawler(&server, false);
+        let filter = QuestionListFilter {
+            tags: vec!["array".to_string()],
+            ..QuestionListFilter::default()
+        };
+
+        let questions = crawler.fetch_problemset_questions(&filter).await.unwrap();
+
+        assert_eq!(questions.len(), 2);
+        assert_eq!(questions[0].title_slug, "two-sum");
+        assert_eq!(questions[0].status(), problemset::QuestionStatus::Solved);
+        assert!(questions[1].is_paid_only);
+        assert_eq!(
+            questions[1].difficulty,
+            question::QuestionDifficulty::Medium
+        );
+        assert_eq!(
+            received_operations(&server).await,
+            ["problemsetQuestionList"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_submission_metas_follows_pages() {
+        let server = MockServer::start().await;
+        mock_graphql(
+            &server,
+            "Submissions",
+            json!({ "offset": 0, "lastKey": "" }),
+            json!({ "data": { "submissionList": {
+                "lastKey": "page-2",
+                "hasNext": true,
+                "submissions": [
+                    { "statusDisplay": "Accepted", "lang": "rust", "url": "/submissions/detail/3/", "timestamp": "1700000300" },
+                    { "statusDisplay": "Wrong Answer", "lang": "rust", "url": "/submissions/detail/2/", "timestamp": "1700000200" }
+                ]
+            } } }),
+        )
+        .await;
+        mock_graphql(
+            &server,
+            "Submissions",
+            json!({ "offset": 2, "lastKey": "page-2" }),
+            json!({ "data": { "submissionList": {
+                "lastKey": null,
+                "hasNext": false,
+                "submissions": [
+                    { "statusDisplay": "Accepted", "lang": "python3", "url": "/submissions/detail/1/", "timestamp": "1700000100" }
+                ]
+            } } }),
+        )
+        .await;
+        let crawler = test_crawler(&server, true);
+
+        let metas = crawler.fetch_submission_metas("two-sum").await.unwrap();
+
+        let urls = metas
+            .iter()
+            .map(|meta| meta.url.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            urls,
+            [
+                "/submissions/detail/3/",
+                "/submissions/detail/2/",
+                "/submissions/detail/1/"
+            ]
+        );
+        assert_eq!(
+            received_operations(&server).await,
+            ["Submissions", "Submissions"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_accepted_submissions_does_not_check_session_again() {
+        let server = MockServer::start().await;
+        mock_graphql(
+            &server,
+            "Submissions",
+            json!({ "questionSlug": "two-sum" }),
+            json!({ "data": { "submissionList": { "lastKey": null, "hasNext": false, "submissions": [
+                { "statusDisplay": "Wrong Answer", "lang": "rust", "url": "/submissions/detail/1/", "timestamp": "1700000000" }
+            ] } } }),
+        )
+        .await;
+        let crawler = test_crawler(&server, true);
+
+        assert!(
+            crawler
+                .fetch_accepted_submissions("two-sum")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(received_operations(&server).await, ["Submissions"]);
+    }
+
+    #[tokio::test]
+    async fn test_crawl_unknown_problem_is_not_found() {
+        let server = MockServer::start().await;
+        for operation in ["getQuestionDetail", "officialSolution"] {
+            mock_graphql(
+                &server,
+                operation,
+                json!({ "titleSlug": "no-such-problem" }),
+                json!({ "data": { "question": null } }),
+            )
+            .await;
+        }
+        let crawler = test_crawler(&server, false);
+
+        assert_eq!(
+            crawler.fetch_editorial("no-such-problem").await.unwrap(),
+            None
+        );
+        assert!(matches!(
+            crawler.crawl_problem("no-such-problem").await,
+            Err(CrawlerError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_operation_name() {
+        assert_eq!(
+            operation_name(
+                r#"query getQuestionDetail($titleSlug:String!){question(titleSlug:$titleSlug){content}}"#
+            ),
+            "getQuestionDetail"
+        );
+        assert_eq!(
+            operation_name(r#"query globalData { userStatus { isSignedIn } }"#),
+            "globalData"
+        );
+        assert_eq!(
+            operation_name("{ userStatus { isSignedIn } }"),
+            "anonymous query"
+        );
+    }
+
+    async fn mock_premium_only_problem(server: &MockServer) {
+        mock_graphql(
+            server,
+            "getQuestionDetail",
+            json!({ "titleSlug": "binary-tree-upside-down" }),
+            json!({ "data": { "question": {
+                "questionFrontendId": "156",
+                "questionTitle": "Binary Tree Upside Down",
+                "questionTitleSlug": "binary-tree-upside-down",
+                "content": null,
+                "difficulty": "Medium",
+                "isPaidOnly": true,
+                "topicTags": [{ "name": "Tree", "slug": "tree" }],
+                "hints": []
+            } } }),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_skip_premium_only_problem_without_further_requests() {
+        let server = MockServer::start().await;
+        mock_premium_only_problem(&server).await;
+        let crawler = test_crawler(&server, true);
+
+        assert!(matches!(
+            crawler.crawl_problem("binary-tree-upside-down").await,
+            Err(CrawlerError::PremiumOnly(_))
+        ));
+        assert_eq!(received_operations(&server).await, ["getQuestionDetail"]);
+    }
+
+    #[tokio::test]
+    async fn test_stub_premium_only_problem() {
+        let server = MockServer::start().await;
+        mock_premium_only_problem(&server).await;
+        mock_graphql(
+            &server,
+            "officialSolution",
+            json!({ "titleSlug": "binary-tree-upside-down" }),
+            json!({ "data": { "question": { "solution": null } } }),
+        )
+        .await;
+        let mut crawler = test_crawler(&server, false);
+        crawler.set_premium_only_policy(PremiumOnlyPolicy::Stub);
+
+        let problem = crawler
+            .crawl_problem("binary-tree-upside-down")
+            .await
+            .unwrap();
+
+        assert_eq!(problem.name, "156. Binary Tree Upside Down");
+        assert!(problem.description.content.contains(
+            r#"<a href="https://leetcode.com/problems/binary-tree-upside-down/">Binary Tree Upside Down</a>"#
+        ));
+        assert_eq!(problem.description.topic_tags[0].slug, "tree");
+        assert_eq!(problem.editorial, None);
+    }
+
+    #[test]
+    fn test_parse_premium_only_question() {
+        let question_obj: QuestionObj = parse_graphql_response(
+            StatusCode::OK,
+            "application/json",
+            br#"{"data":{"question":{"questionFrontendId":"156","questionTitle":"Binary Tree Upside Down","questionTitleSlug":"binary-tree-upside-down","content":null,"difficulty":"Medium","isPaidOnly":true,"topicTags":[],"hints":[]}}}"#,
+        )
+        .unwrap();
+        let question = question_obj.data.question.unwrap();
+
+        assert!(question.is_paid_only);
+        assert!(question.content.is_empty());
+    }
 }
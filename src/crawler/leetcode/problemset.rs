@@ -0,0 +1,167 @@
+use super::deserialize_vec_or_default;
+use super::question::{QuestionDifficulty, TopicTag};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fmt;
+
+#[derive(Debug, Deserialize)]
+pub struct ProblemsetObj {
+    pub data: ProblemsetData,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProblemsetData {
+    pub problemset_question_list: ProblemsetQuestionList,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProblemsetQuestionList {
+    pub total: usize,
+    #[serde(default, deserialize_with = "deserialize_vec_or_default")]
+    pub questions: Vec<ProblemsetQuestion>,
+}
+
+/// A single entry of the LeetCode problemset question list.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProblemsetQuestion {
+    pub frontend_question_id: String,
+    pub title: String,
+    pub title_slug: String,
+    pub difficulty: QuestionDifficulty,
+    pub is_paid_only: bool,
+    pub ac_rate: f64,
+    /// The solving status of the signed-in user, `None` if the question was never attempted.
+    #[serde(default)]
+    pub status: Option<QuestionStatus>,
+    #[serde(default, deserialize_with = "deserialize_vec_or_default")]
+    pub topic_tags: Vec<TopicTag>,
+}
+
+impl ProblemsetQuestion {
+    pub fn status(&self) -> QuestionStatus {
+        self.status.unwrap_or(QuestionStatus::Todo)
+    }
+}
+
+/// The solving status of a question for the signed-in user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuestionStatus {
+    #[serde(rename = "ac")]
+    Solved,
+    #[serde(rename = "notac")]
+    Attempted,
+    #[serde(rename = "todo", other)]
+    Todo,
+}
+
+/// Filters applied to the problemset question list query.
+#[derive(Debug, Default)]
+pub struct QuestionListFilter {
+    pub tags: Vec<String>,
+    pub difficulty: Option<QuestionDifficulty>,
+    pub status: Option<QuestionStatus>,
+}
+
+impl QuestionListFilter {
+    /// Builds the `filters` variable of the problemset question list query.
+    pub fn to_variables(&self) -> serde_json::Value {
+        let mut filters = serde_json::Map::new();
+
+        if !self.tags.is_empty() {
+            filters.insert("tags".to_string(), json!(self.tags));
+        }
+
+        if let Some(difficulty) = &self.difficulty {
+            filters.insert(
+                "difficulty".to_string(),
+                json!(difficulty.to_string().to_uppercase()),
+            );
+        }
+
+        if let Some(status) = self.status {
+            let status = match status {
+                QuestionStatus::Solved => "AC",
+                QuestionStatus::Attempted => "TRIED",
+                QuestionStatus::Todo => "NOT_STARTED",
+            };
+            filters.insert("status".to_string(), json!(status));
+        }
+
+        serde_json::Value::Object(filters)
+    }
+
+    /// Returns true if the question satisfies every filter, mirroring what the API would return.
+    pub fn matches(&self, question: &ProblemsetQuestion) -> bool {
+        let has_tags = self.tags.iter().all(|tag| {
+            question
+                .topic_tags
+                .iter()
+                .any(|topic_tag| &topic_tag.slug == tag)
+        });
+
+        has_tags
+            && self
+                .difficulty
+                .is_none_or(|difficulty| difficulty == question.difficulty)
+            && self.status.is_none_or(|status| status == question.status())
+    }
+}
+
+impl fmt::Display for QuestionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuestionStatus::Solved => write!(f, "Solved"),
+            QuestionStatus::Attempted => write!(f, "Attempted"),
+            QuestionStatus::Todo => write!(f, "Todo"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_question_list_filter_to_variables() {
+        let filter = QuestionListFilter {
+            tags: vec!["dynamic-programming".to_string()],
+            difficulty: Some(QuestionDifficulty::Medium),
+            status: Some(QuestionStatus::Solved),
+        };
+
+        assert_eq!(
+            filter.to_variables(),
+            json!({ "tags": ["dynamic-programming"], "difficulty": "MEDIUM", "status": "AC" })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_problemset_question_status() {
+        let question: ProblemsetQuestion = serde_json::from_str(
+            r#"{"frontendQuestionId":"1","title":"Two Sum","titleSlug":"two-sum","difficulty":"Easy","isPaidOnly":false,"acRate":55.5,"status":"ac","topicTags":[{"name":"Array","slug":"array"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(question.status(), QuestionStatus::Solved);
+
+        let filter = QuestionListFilter {
+            tags: vec!["array".to_string()],
+            difficulty: Some(QuestionDifficulty::Easy),
+            status: Some(QuestionStatus::Solved),
+        };
+        assert!(filter.matches(&question));
+
+        let question: ProblemsetQuestion = serde_json::from_str(
+            r#"{"frontendQuestionId":"1","title":"Two Sum","titleSlug":"two-sum","difficulty":"Easy","isPaidOnly":false,"acRate":55.5,"status":null,"topicTags":[]}"#,
+        )
+        .unwrap();
+        assert_eq!(question.status(), QuestionStatus::Todo);
+        assert!(!filter.matches(&question));
+    }
+
+    #[test]
+    fn test_empty_question_list_filter_to_variables() {
+        assert_eq!(QuestionListFilter::default().to_variables(), json!({}));
+    }
+}
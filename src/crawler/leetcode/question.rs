@@ -1,6 +1,7 @@
 use crate::markdown::ToMarkdown;
 use std::fmt;
 
+use super::deserialize_vec_or_default;
 use lol_html::html_content::ContentType;
 use lol_html::{RewriteStrSettings, element, rewrite_str};
 use serde::{Deserialize, Deserializer, Serialize};
@@ -12,7 +13,25 @@ pub struct QuestionObj {
 
 #[derive(Debug, Deserialize)]
 pub struct QuestionData {
-    pub question: QuestionDetail,
+    /// `None` when no problem has the requested slug.
+    pub question: Option<QuestionDetail>,
+}
+
+/// Response of a query that only asks for the slug, to check that a problem exists.
+#[derive(Debug, Deserialize)]
+pub struct QuestionSlugObj {
+    pub data: QuestionSlugData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuestionSlugData {
+    pub question: Option<QuestionSlug>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionSlug {
+    pub title_slug: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -21,13 +40,20 @@ pub struct QuestionDetail {
     pub question_frontend_id: String,
     pub question_title: String,
     pub question_title_slug: String,
+    /// Description in HTML, empty when the session may not see it (premium-only problems).
+    #[serde(default, deserialize_with = "deserialize_string_or_default")]
     pub content: String,
     pub difficulty: QuestionDifficulty,
+    #[serde(default)]
+    pub is_paid_only: bool,
     #[serde(default, deserialize_with = "deserialize_vec_or_default")]
     pub topic_tags: Vec<TopicTag>,
+    /// Hints in HTML, as shown below the description on the website.
+    #[serde(default, deserialize_with = "deserialize_vec_or_default")]
+    pub hints: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum QuestionDifficulty {
     Easy,
     Medium,
@@ -42,12 +68,11 @@ pub struct TopicTag {
     pub slug: String,
 }
 
-fn deserialize_vec_or_default<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+fn deserialize_string_or_default<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
     D: Deserializer<'de>,
-    T: Deserialize<'de>,
 {
-    Ok(Option::<Vec<T>>::deserialize(deserializer)?.unwrap_or_default())
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
 }
 
 impl ToMarkdown for QuestionDetail {
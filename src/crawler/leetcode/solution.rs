@@ -1,4 +1,8 @@
+use lol_html::html_content::ContentType;
+use lol_html::{RewriteStrSettings, element, rewrite_str};
 use serde::{Deserialize, Serialize};
+use tracing::debug;
+use url::Url;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SolutionObj {
@@ -7,38 +11,153 @@ pub struct SolutionObj {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SolutionData {
-    pub question: SolutionQuestion,
+    /// `None` when no problem has the requested slug.
+    pub question: Option<SolutionQuestion>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SolutionQuestion {
-    pub question_id: String,
-    pub article: String,
-    pub solution: SolutionDetail,
-    #[serde(rename = "__typename")]
-    pub typename: String,
+    #[serde(default)]
+    pub solution: Option<SolutionDetail>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SolutionDetail {
     pub id: String,
-    pub content: String,
-    pub content_type_id: String,
+    #[serde(default)]
+    pub content: Option<String>,
     pub can_see_detail: bool,
     pub paid_only: bool,
-    pub rating: SolutionRating,
-    #[serde(rename = "__typename")]
-    pub typename: String,
+    #[serde(default)]
+    pub rating: Option<SolutionRating>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SolutionRating {
-    pub id: String,
     pub count: u32,
     pub average: String,
-    #[serde(rename = "__typename")]
-    pub typename: String,
+}
+
+impl SolutionDetail {
+    /// Returns the editorial content in Markdown, if it is visible to the current session.
+    pub fn visible_content(&self) -> Option<&str> {
+        self.content
+            .as_deref()
+            .filter(|content| self.can_see_detail && !content.trim().is_empty())
+    }
+}
+
+/// Makes the image URLs of editorial Markdown absolute, as they are relative to the editorial page
+/// and broken in Anki and in the saved Markdown files. HTML images become Markdown images, and
+/// embedded code playgrounds, which only work on LeetCode, become links, since Anki omits raw HTML.
+pub fn resolve_editorial_urls(content: &str, base: &Url) -> String {
+    let element_content_handlers = vec![
+        element!("img[src]", |el| {
+            let src = resolve_url(base, &el.get_attribute("src").unwrap_or_default());
+            let alt = el
+                .get_attribute("alt")
+                .unwrap_or_default()
+                .replace(['[', ']'], "");
+            el.replace(&format!("![{}]({})", alt, src), ContentType::Html);
+
+            Ok(())
+        }),
+        element!("iframe", |el| {
+            match el.get_attribute("src") {
+                Some(src) => el.replace(
+                    &format!("[Open the code on LeetCode]({})", resolve_url(base, &src)),
+                    ContentType::Html,
+                ),
+                None => el.remove(),
+            }
+
+            Ok(())
+        }),
+    ];
+
+    let content = match rewrite_str(
+        content,
+        RewriteStrSettings {
+            element_content_handlers,
+            ..RewriteStrSettings::new()
+        },
+    ) {
+        Ok(rewritten) => rewritten,
+        Err(e) => {
+            debug!("Failed to rewrite the HTML of an editorial: {}", e);
+            content.to_string()
+        }
+    };
+
+    resolve_markdown_images(&content, base)
+}
+
+/// Resolves the URL of every Markdown image, `![alt](url)`, against `base`.
+fn resolve_markdown_images(content: &str, base: &Url) -> String {
+    let mut resolved = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find("![") {
+        let url_start = rest[start..]
+            .find("](")
+            .map(|offset| start + offset + 2)
+            .filter(|&url_start| !rest[start..url_start].contains('\n'));
+        let Some(url_start) = url_start else {
+            resolved.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            continue;
+        };
+        let url_end = rest[url_start..]
+            .find(|c: char| c == ')' || c.is_whitespace())
+            .map_or(rest.len(), |offset| url_start + offset);
+
+        resolved.push_str(&rest[..url_start]);
+        resolved.push_str(&resolve_url(base, &rest[url_start..url_end]));
+        rest = &rest[url_end..];
+    }
+    resolved.push_str(rest);
+
+    resolved
+}
+
+fn resolve_url(base: &Url, url: &str) -> String {
+    base.join(url)
+        .map(String::from)
+        .unwrap_or_else(|_| url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_editorial_urls() {
+        let base = Url::parse("https://leetcode.com/problems/two-sum/solution/").unwrap();
+        let content = r#"## Approach 1
+
+![Two pointers](../Figures/1/two_pointers.png "Figure 1")
+
+<img src="/problems/two-sum/Figures/1/hash.png" alt="Hash [map]" />
+
+<iframe src="https://leetcode.com/playground/Abc123/shared" frameBorder="0" width="100%" height="500" name="Abc123"></iframe>
+
+Keep ![absolute](https://assets.leetcode.com/a.png) and `vector<int>` as they are. ![broken
+"#;
+
+        assert_eq!(
+            resolve_editorial_urls(content, &base),
+            r#"## Approach 1
+
+![Two pointers](https://leetcode.com/problems/two-sum/Figures/1/two_pointers.png "Figure 1")
+
+![Hash map](https://leetcode.com/problems/two-sum/Figures/1/hash.png)
+
+[Open the code on LeetCode](https://leetcode.com/playground/Abc123/shared)
+
+Keep ![absolute](https://assets.leetcode.com/a.png) and `vector<int>` as they are. ![broken
+"#
+        );
+    }
 }
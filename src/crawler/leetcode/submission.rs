@@ -1,3 +1,4 @@
+use super::deserialize_vec_or_default;
 use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -14,6 +15,11 @@ pub struct SubmissionData {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SubmissionList {
+    /// Cursor of the next page, passed back as `lastKey`.
+    #[serde(default)]
+    pub last_key: Option<String>,
+    #[serde(default)]
+    pub has_next: bool,
     #[serde(default, deserialize_with = "deserialize_vec_or_default")]
     pub submissions: Vec<SubmissionMeta>,
 }
@@ -24,18 +30,58 @@ pub struct SubmissionMeta {
     pub status_display: String,
     pub lang: String,
     pub url: String,
+    /// Unix timestamp (in seconds) of the submission.
+    #[serde(default, deserialize_with = "deserialize_timestamp")]
+    pub timestamp: i64,
 }
 
 impl SubmissionMeta {
     pub fn is_accepted(&self) -> bool {
         self.status_display == "Accepted"
     }
+
+    /// Returns true if the submission was made within `[start, end]` (Unix timestamps in seconds).
+    pub fn submitted_between(&self, start: i64, end: i64) -> bool {
+        (start..=end).contains(&self.timestamp)
+    }
 }
 
-fn deserialize_vec_or_default<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+/// LeetCode returns submission timestamps as strings, but accept plain numbers as well.
+fn deserialize_timestamp<'de, D>(deserializer: D) -> Result<i64, D::Error>
 where
     D: Deserializer<'de>,
-    T: Deserialize<'de>,
 {
-    Ok(Option::<Vec<T>>::deserialize(deserializer)?.unwrap_or_default())
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Timestamp {
+        Number(i64),
+        Text(String),
+    }
+
+    match Timestamp::deserialize(deserializer)? {
+        Timestamp::Number(timestamp) => Ok(timestamp),
+        Timestamp::Text(text) => text.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_submission_meta_timestamp() {
+        let meta: SubmissionMeta = serde_json::from_str(
+            r#"{"statusDisplay":"Accepted","lang":"python3","url":"/submissions/detail/1/","timestamp":"1700000000"}"#,
+        )
+        .unwrap();
+        assert_eq!(meta.timestamp, 1700000000);
+        assert!(meta.submitted_between(1700000000, 1700005400));
+        assert!(!meta.submitted_between(1700000001, 1700005400));
+
+        let meta: SubmissionMeta = serde_json::from_str(
+            r#"{"statusDisplay":"Accepted","lang":"python3","url":"/submissions/detail/1/","timestamp":42}"#,
+        )
+        .unwrap();
+        assert_eq!(meta.timestamp, 42);
+    }
 }
@@ -0,0 +1,80 @@
+use super::{LeetCodeCrawler, LeetCodeSite, operation_name};
+use crate::config::{LeetCodeCookies, NetworkConfig, RateLimit, RateLimits};
+use serde_json::{Value, json};
+use std::str::FromStr;
+use url::Url;
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Match, Mock, MockServer, Request, ResponseTemplate};
+
+/// A crawler sending its GraphQL requests to `server`, without rate limit delays.
+pub(crate) fn test_crawler(server: &MockServer, signed_in: bool) -> LeetCodeCrawler {
+    let rate_limit = RateLimit {
+        requests_per_second: 1000.0,
+        burst: 1000,
+        jitter_min_ms: 0,
+        jitter_max_ms: 0,
+    };
+    let rate_limits = RateLimits {
+        graphql: rate_limit.clone(),
+        web_driver: rate_limit,
+    };
+    let cookie = signed_in
+        .then(|| LeetCodeCookies::from_str("csrftoken=csrf; LEETCODE_SESSION=session").unwrap());
+    let mut crawler = LeetCodeCrawler::with_reqwest_client(
+        &rate_limits,
+        LeetCodeSite::Com,
+        &NetworkConfig::default(),
+        cookie.as_ref(),
+    )
+    .unwrap();
+    crawler.api = Url::parse(&server.uri()).unwrap();
+
+    crawler
+}
+
+/// Matches the GraphQL requests of one operation, e.g. `getQuestionDetail`.
+struct GraphQlOperation(String);
+
+impl Match for GraphQlOperation {
+    fn matches(&self, request: &Request) -> bool {
+        request
+            .body_json::<Value>()
+            .ok()
+            .and_then(|body| {
+                body["query"]
+                    .as_str()
+                    .map(|query| operation_name(query) == self.0)
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Answers the requests of the GraphQL `operation` whose variables contain `variables` with
+/// `response`.
+pub(crate) async fn mock_graphql(
+    server: &MockServer,
+    operation: &str,
+    variables: Value,
+    response: Value,
+) {
+    Mock::given(method("POST"))
+        .and(GraphQlOperation(operation.to_string()))
+        .and(body_partial_json(json!({ "variables": variables })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(server)
+        .await;
+}
+
+/// Returns the operation names of the GraphQL requests `server` received, in order.
+pub(crate) async fn received_operations(server: &MockServer) -> Vec<String> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter_map(|request| {
+            let body = request.body_json::<Value>().ok()?;
+            Some(operation_name(body["query"].as_str()?).to_string())
+        })
+        .collect()
+}
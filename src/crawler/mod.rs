@@ -1,4 +1,8 @@
+pub mod challenge;
 pub mod leetcode;
+pub mod web_driver;
+
+use crate::metrics;
 
 pub async fn retry<F, Fut, T, E>(times: usize, mut f: F) -> Result<T, E>
 where
@@ -9,7 +13,7 @@ where
         match f().await {
             Ok(val) => return Ok(val),
             Err(err) if attempt == times => return Err(err),
-            Err(_) => {}
+            Err(_) => metrics::count(metrics::RETRIES),
         }
     }
 
@@ -0,0 +1,817 @@
+use crate::config::{NetworkConfig, WebDriverConfig};
+use crate::error::CrawlerError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream, ToSocketAddrs};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::process::{Child, Command};
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tokio::time::sleep;
+use tracing::{debug, info, warn};
+use url::Url;
+
+const DRIVER_READY_TIMEOUT: Duration = Duration::from_secs(15);
+const DRIVER_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const SESSION_DELETE_TIMEOUT: Duration = Duration::from_secs(2);
+const SESSIONS_DIR_NAME: &str = "lanci/webdriver-sessions";
+
+/// The browser driven through WebDriver, which decides the capabilities sent to the driver.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebDriverBrowser {
+    /// Firefox through geckodriver.
+    #[default]
+    Firefox,
+    /// Google Chrome through chromedriver.
+    Chrome,
+    /// Chromium through chromedriver.
+    Chromium,
+}
+
+/// A geckodriver or chromedriver process started by lanci. The process is killed when this is dropped.
+pub struct WebDriverProcess {
+    child: Child,
+    endpoint: Url,
+}
+
+impl WebDriverProcess {
+    /// Starts the driver binary on a free local port and waits until it accepts new sessions.
+    pub async fn spawn(driver: &Path) -> Result<Self, CrawlerError> {
+        let port = free_port().map_err(|e| CrawlerError::WebDriverSpawnError(e.to_string()))?;
+        let endpoint = Url::parse(&format!("http://127.0.0.1:{}", port))?;
+
+        info!("Starting {} on port {}", driver.display(), port);
+        // geckodriver and chromedriver both accept `--port=<port>`
+        let child = Command::new(driver)
+            .arg(format!("--port={}", port))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                CrawlerError::WebDriverSpawnError(format!("{}: {}", driver.display(), e))
+            })?;
+
+        let mut process = Self { child, endpoint };
+        process.wait_until_ready().await?;
+
+        Ok(process)
+    }
+
+    /// The endpoint to connect the web driver client to.
+    pub fn endpoint(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// Stops the driver process and waits for it to exit.
+    pub async fn shutdown(mut self) -> Result<(), CrawlerError> {
+        debug!("Stopping the WebDriver process");
+        self.child
+            .kill()
+            .await
+            .map_err(|e| CrawlerError::WebDriverSpawnError(e.to_string()))
+    }
+
+    /// Polls the driver's `/status` endpoint until it reports being ready.
+    async fn wait_until_ready(&mut self) -> Result<(), CrawlerError> {
+        #[derive(Deserialize)]
+        struct StatusResponse {
+            value: StatusValue,
+        }
+
+        #[derive(Deserialize)]
+        struct StatusValue {
+            ready: bool,
+        }
+
+        let client = reqwest::Client::new();
+        let status_url = self.endpoint.join("status")?;
+        let started = Instant::now();
+
+        while started.elapsed() < DRIVER_READY_TIMEOUT {
+            if let Ok(Some(status)) = self.child.try_wait() {
+                return Err(CrawlerError::WebDriverSpawnError(format!(
+                    "the driver exited with {}",
+                    status
+                )));
+            }
+
+            if let Ok(response) = client.get(status_url.clone()).send().await
+                && let Ok(status) = response.json::<StatusResponse>().await
+                && status.value.ready
+            {
+                return Ok(());
+            }
+
+            sleep(DRIVER_POLL_INTERVAL).await;
+        }
+
+        Err(CrawlerError::WebDriverSpawnError(format!(
+            "the driver was not ready after {} seconds",
+            DRIVER_READY_TIMEOUT.as_secs()
+        )))
+    }
+}
+
+/// Asks the OS for a currently unused local port.
+fn free_port() -> std::io::Result<u16> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    Ok(listener.local_addr()?.port())
+}
+
+/// A web driver session that is deleted even when it is not closed explicitly, e.g. when the crawl
+/// panics or is interrupted. Callers close it with [`WebDriverSession::close`]; deleting it on drop
+/// is only a best-effort fallback. Dereferences to the underlying `fantoccini::Client`.
+pub struct WebDriverSession {
+    client: Option<fantoccini::Client>,
+    session: Option<RecordedSession>,
+}
+
+impl WebDriverSession {
+    /// Starts a new web driver session for the configured browser. `headless` overrides the
+    /// configured mode, so that callers needing a visible window can force it. The extra network
+    /// headers are added to browser requests where the browser supports it.
+    pub async fn connect(
+        web_driver_config: &WebDriverConfig,
+        network: &NetworkConfig,
+        headless: bool,
+    ) -> Result<Self, CrawlerError> {
+        let mut web_driver_builder = fantoccini::ClientBuilder::native();
+        web_driver_builder.capabilities(capabilities(web_driver_config, network, headless));
+
+        let client = web_driver_builder
+            .connect(web_driver_config.endpoint.as_str())
+            .await?;
+        let session = client
+            .session_id()
+            .await?
+            .map(|session_id| RecordedSession {
+                endpoint: web_driver_config.endpoint.clone(),
+                session_id,
+            });
+        if let Some(session) = &session {
+            debug!("Started web driver session {}", session.session_id);
+            record_session(&sessions_dir(), session);
+        }
+        let web_driver = Self {
+            client: Some(client),
+            session,
+        };
+
+        if !network.ca_certificates.is_empty() {
+            info!(
+                "The browser accepts any TLS certificate, as WebDriver cannot pass it network.ca_certificates"
+            );
+        }
+        if !network.headers.is_empty()
+            && let Err(e) =
+                set_extra_headers(&web_driver, web_driver_config.browser, &network.headers).await
+        {
+            warn!(
+                "Failed to add network.headers to browser requests, they are only sent with API requests: {}",
+                e
+            );
+        }
+
+        Ok(web_driver)
+    }
+
+    /// Closes the browser and ends the session.
+    pub async fn close(mut self) -> Result<(), CrawlerError> {
+        let client = self
+            .client
+            .take()
+            .expect("client is only taken when closing");
+        client.close().await?;
+        if let Some(session) = self.session.take() {
+            forget_session(&sessions_dir(), &session);
+        }
+
+        Ok(())
+    }
+}
+
+impl Deref for WebDriverSession {
+    type Target = fantoccini::Client;
+
+    fn deref(&self) -> &Self::Target {
+        self.client
+            .as_ref()
+            .expect("client is only taken when closing")
+    }
+}
+
+impl Drop for WebDriverSession {
+    fn drop(&mut self) {
+        let Some(session) = self.session.take() else {
+            return;
+        };
+        if self.client.is_none() {
+            return;
+        }
+
+        // Nothing can be awaited here, and the runtime may be shutting down, so the session is
+        // deleted with a short blocking request instead of through the client. On a worker thread,
+        // the runtime is told first, so that it moves the other tasks elsewhere meanwhile.
+        warn!(
+            "Web driver session {} was not closed, deleting it",
+            session.session_id
+        );
+        let delete = || delete_session_blocking(&session.endpoint, &session.session_id);
+        let result = match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(delete)
+            }
+            _ => delete(),
+        };
+        match result {
+            Ok(()) => forget_session(&sessions_dir(), &session),
+            Err(e) => warn!(
+                "Failed to delete web driver session {}: {}. Run `lanci webdriver cleanup` to remove it.",
+                session.session_id, e
+            ),
+        }
+    }
+}
+
+/// A session started by lanci, recorded in its own file in a per-user directory until it is closed,
+/// so that `lanci webdriver cleanup` can find sessions left behind by a killed process.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedSession {
+    pub endpoint: Url,
+    pub session_id: String,
+}
+
+impl RecordedSession {
+    fn file_path(&self, dir: &Path) -> PathBuf {
+        let file_stem = self
+            .session_id
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect::<String>();
+
+        dir.join(format!("{}.json", file_stem))
+    }
+}
+
+/// The directory of the session records: `lanci/webdriver-sessions` under `$XDG_STATE_HOME`,
+/// `~/.local/state` or `%LOCALAPPDATA%`, so that the runs of other users never share it.
+fn sessions_dir() -> PathBuf {
+    let state_dir = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))
+        .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .unwrap_or_else(std::env::temp_dir);
+
+    state_dir.join(SESSIONS_DIR_NAME)
+}
+
+/// Writes the record of a session. Every session has its own file, so concurrent runs never
+/// overwrite each other's records. The record is best effort, so failures are only logged.
+fn record_session(dir: &Path, session: &RecordedSession) {
+    let mut dir_builder = std::fs::DirBuilder::new();
+    dir_builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        dir_builder.mode(0o700);
+    }
+
+    let result = dir_builder.create(dir).and_then(|()| {
+        let content = serde_json::to_string(session).map_err(io::Error::other)?;
+        std::fs::write(session.file_path(dir), content)
+    });
+    if let Err(e) = result {
+        debug!("Failed to record web driver session: {}", e);
+    }
+}
+
+/// Removes the record of a session that is closed or deleted.
+fn forget_session(dir: &Path, session: &RecordedSession) {
+    if let Err(e) = std::fs::remove_file(session.file_path(dir))
+        && e.kind() != io::ErrorKind::NotFound
+    {
+        debug!("Failed to remove web driver session record: {}", e);
+    }
+}
+
+fn recorded_sessions(dir: &Path) -> Vec<RecordedSession> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| std::fs::read_to_string(entry.ok()?.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect()
+}
+
+/// Deletes the sessions on the endpoint that lanci recorded but never closed. Sessions of other
+/// processes and users of the same endpoint are left alone. Returns how many were deleted.
+pub async fn cleanup_sessions(endpoint: &Url) -> Result<usize, CrawlerError> {
+    cleanup_recorded_sessions(&sessions_dir(), endpoint).await
+}
+
+async fn cleanup_recorded_sessions(dir: &Path, endpoint: &Url) -> Result<usize, CrawlerError> {
+    let client = reqwest::Client::new();
+    let sessions = recorded_sessions(dir)
+        .into_iter()
+        .filter(|session| session.endpoint == *endpoint)
+        .collect::<Vec<_>>();
+
+    let mut deleted = 0;
+    for session in &sessions {
+        let response = client
+            .delete(endpoint.join(&format!("session/{}", session.session_id))?)
+            .send()
+            .await?;
+        if response.status().is_success() {
+            info!("Deleted web driver session {}", session.session_id);
+            deleted += 1;
+        } else {
+            debug!(
+                "Web driver session {} could not be deleted: HTTP {}",
+                session.session_id,
+                response.status()
+            );
+        }
+        // A session the driver no longer knows is gone as well
+        forget_session(dir, session);
+    }
+
+    Ok(deleted)
+}
+
+/// Deletes a session with a plain blocking HTTP request, usable where nothing can be awaited.
+fn delete_session_blocking(endpoint: &Url, session_id: &str) -> io::Result<()> {
+    let url = endpoint
+        .join(&format!("session/{}", session_id))
+        .map_err(io::Error::other)?;
+    if url.scheme() != "http" {
+        return Err(io::Error::other(format!(
+            "only http endpoints are supported, got {}",
+            url.scheme()
+        )));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| io::Error::other("endpoint has no host"))?;
+    let port = url.port_or_known_default().unwrap_or(80);
+
+    let address = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::other("endpoint host has no address"))?;
+    let mut stream = TcpStream::connect_timeout(&address, SESSION_DELETE_TIMEOUT)?;
+    stream.set_read_timeout(Some(SESSION_DELETE_TIMEOUT))?;
+    stream.set_write_timeout(Some(SESSION_DELETE_TIMEOUT))?;
+    write!(
+        stream,
+        "DELETE {} HTTP/1.1\r\nHost: {}:{}\r\nConnection: close\r\nContent-Length: 0\r\n\r\n",
+        url.path(),
+        host,
+        port
+    )?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line)?;
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        _ => Err(io::Error::other(format!(
+            "unexpected response `{}`",
+            status_line.trim()
+        ))),
+    }
+}
+
+/// A Chrome DevTools Protocol command, sent through chromedriver's `goog/cdp/execute` extension.
+#[derive(Debug)]
+struct DevToolsCommand {
+    cmd: &'static str,
+    params: Value,
+}
+
+impl fantoccini::wd::WebDriverCompatibleCommand for DevToolsCommand {
+    fn endpoint(&self, base_url: &Url, session_id: Option<&str>) -> Result<Url, url::ParseError> {
+        base_url.join(&format!(
+            "session/{}/goog/cdp/execute",
+            session_id.unwrap_or_default()
+        ))
+    }
+
+    fn method_and_body(&self, _request_url: &Url) -> (reqwest::Method, Option<String>) {
+        let body = json!({ "cmd": self.cmd, "params": self.params });
+
+        (reqwest::Method::POST, Some(body.to_string()))
+    }
+}
+
+/// Adds headers to every request of the browser. Only Chrome and Chromium support this, through
+/// the DevTools Protocol; Firefox sends the headers with API requests only.
+async fn set_extra_headers(
+    web_driver: &fantoccini::Client,
+    browser: WebDriverBrowser,
+    headers: &BTreeMap<String, String>,
+) -> Result<(), CrawlerError> {
+    if browser == WebDriverBrowser::Firefox {
+        warn!(
+            "Firefox cannot add network.headers to browser requests, they are only sent with API requests"
+        );
+        return Ok(());
+    }
+
+    web_driver
+        .issue_cmd(DevToolsCommand {
+            cmd: "Network.enable",
+            params: json!({}),
+        })
+        .await?;
+    web_driver
+        .issue_cmd(DevToolsCommand {
+            cmd: "Network.setExtraHTTPHeaders",
+            params: json!({ "headers": headers }),
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Saves what the browser currently shows into a new `name` directory under `dir`: a screenshot,
+/// the page HTML and the current URL. Returns the directory the artifacts were written to.
+pub async fn save_debug_artifacts(
+    web_driver: &fantoccini::Client,
+    dir: &Path,
+    name: &str,
+) -> Result<PathBuf, CrawlerError> {
+    let artifacts_dir = dir.join(name);
+    tokio::fs::create_dir_all(&artifacts_dir)
+        .await
+        .map_err(CrawlerError::DebugArtifactsError)?;
+
+    let url = web_driver.current_url().await?;
+    tokio::fs::write(artifacts_dir.join("url.txt"), url.as_str())
+        .await
+        .map_err(CrawlerError::DebugArtifactsError)?;
+    let source = web_driver.source().await?;
+    tokio::fs::write(artifacts_dir.join("page.html"), source)
+        .await
+        .map_err(CrawlerError::DebugArtifactsError)?;
+    let screenshot = web_driver.screenshot().await?;
+    tokio::fs::write(artifacts_dir.join("screenshot.png"), screenshot)
+        .await
+        .map_err(CrawlerError::DebugArtifactsError)?;
+
+    Ok(artifacts_dir)
+}
+
+/// Builds the session capabilities: the browser name, its vendor options (headless mode, profile
+/// directory, binary, user agent and extra arguments), the proxy, page load timeout and TLS
+/// settings, then the extra capabilities from the config on top. WebDriver cannot add CA
+/// certificates to the browser, so extra ones make it accept any certificate instead.
+pub fn capabilities(
+    web_driver_config: &WebDriverConfig,
+    network: &NetworkConfig,
+    headless: bool,
+) -> Map<String, Value> {
+    let mut args = Vec::new();
+    let mut options = Map::new();
+
+    let (browser_name, options_key) = match web_driver_config.browser {
+        WebDriverBrowser::Firefox => {
+            if headless {
+                args.push("-headless".to_string());
+            }
+            if let Some(user_data_dir) = &web_driver_config.user_data_dir {
+                args.push("-profile".to_string());
+                args.push(user_data_dir.display().to_string());
+            }
+            if let Some(user_agent) = &network.user_agent {
+                options.insert(
+                    "prefs".to_string(),
+                    json!({ "general.useragent.override": user_agent }),
+                );
+            }
+            ("firefox", "moz:firefoxOptions")
+        }
+        WebDriverBrowser::Chrome | WebDriverBrowser::Chromium => {
+            if headless {
+                args.push("--headless=new".to_string());
+            }
+            if let Some(user_data_dir) = &web_driver_config.user_data_dir {
+                args.push(format!("--user-data-dir={}", user_data_dir.display()));
+            }
+            if let Some(user_agent) = &network.user_agent {
+                args.push(format!("--user-agent={}", user_agent));
+            }
+            ("chrome", "goog:chromeOptions")
+        }
+    };
+    args.extend(web_driver_config.args.iter().cloned());
+
+    if !args.is_empty() {
+        options.insert("args".to_string(), json!(args));
+    }
+    if let Some(binary) = &web_driver_config.binary {
+        options.insert("binary".to_string(), json!(binary.display().to_string()));
+    }
+
+    let mut capabilities = Map::new();
+    capabilities.insert("browserName".to_string(), json!(browser_name));
+    if !options.is_empty() {
+        capabilities.insert(options_key.to_string(), Value::Object(options));
+    }
+    if let Some(proxy) = &network.proxy {
+        capabilities.insert("proxy".to_string(), proxy_capability(proxy));
+    }
+    if let Some(timeout_secs) = network.timeout_secs {
+        capabilities.insert(
+            "timeouts".to_string(),
+            json!({ "pageLoad": timeout_secs * 1000 }),
+        );
+    }
+    if !network.ca_certificates.is_empty() {
+        capabilities.insert("acceptInsecureCerts".to_string(), json!(true));
+    }
+    for (key, value) in &web_driver_config.capabilities {
+        merge_capability(&mut capabilities, key, value.clone());
+    }
+
+    capabilities
+}
+
+/// Builds the W3C `proxy` capability, which takes `host:port` rather than a URL.
+fn proxy_capability(proxy: &Url) -> Value {
+    let address = match (proxy.host_str(), proxy.port_or_known_default()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        _ => proxy.to_string(),
+    };
+
+    match proxy.scheme() {
+        "socks4" | "socks4a" => {
+            json!({ "proxyType": "manual", "socksProxy": address, "socksVersion": 4 })
+        }
+        "socks5" | "socks5h" => {
+            json!({ "proxyType": "manual", "socksProxy": address, "socksVersion": 5 })
+        }
+        _ => json!({ "proxyType": "manual", "httpProxy": address, "sslProxy": address }),
+    }
+}
+
+/// Inserts a capability, merging JSON objects key by key and appending to arrays, so that extra
+/// capabilities can add browser options without replacing the generated ones.
+fn merge_capability(capabilities: &mut Map<String, Value>, key: &str, value: Value) {
+    match (capabilities.get_mut(key), value) {
+        (Some(Value::Object(existing)), Value::Object(extra)) => {
+            for (key, value) in extra {
+                merge_capability(existing, &key, value);
+            }
+        }
+        (Some(Value::Array(existing)), Value::Array(extra)) => existing.extend(extra),
+        (_, value) => {
+            capabilities.insert(key.to_string(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn web_driver_config(json: &str) -> WebDriverConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_delete_session_blocking_sends_delete_request() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let endpoint = Url::parse(&format!(
+            "http://127.0.0.1:{}/wd/hub/",
+            listener.local_addr().unwrap().port()
+        ))
+        .unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request_line = String::new();
+            BufReader::new(&stream)
+                .read_line(&mut request_line)
+                .unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 14\r\n\r\n{\"value\":null}")
+                .unwrap();
+            request_line
+        });
+
+        delete_session_blocking(&endpoint, "abc123").unwrap();
+        assert_eq!(
+            server.join().unwrap().trim(),
+            "DELETE /wd/hub/session/abc123 HTTP/1.1"
+        );
+    }
+
+    #[test]
+    fn test_session_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions_dir = dir.path().join("sessions");
+        let endpoint = Url::parse("http://localhost:4444").unwrap();
+        let first = RecordedSession {
+            endpoint: endpoint.clone(),
+            session_id: "abc-123".to_string(),
+        };
+        let second = RecordedSession {
+            endpoint,
+            session_id: "../def".to_string(),
+        };
+
+        record_session(&sessions_dir, &first);
+        record_session(&sessions_dir, &second);
+        assert_eq!(
+            second.file_path(&sessions_dir),
+            sessions_dir.join("def.json")
+        );
+        let mut sessions = recorded_sessions(&sessions_dir);
+        sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        assert_eq!(sessions, vec![second.clone(), first.clone()]);
+
+        forget_session(&sessions_dir, &second);
+        forget_session(&sessions_dir, &second);
+        assert_eq!(recorded_sessions(&sessions_dir), vec![first]);
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_deletes_only_recorded_sessions_of_the_endpoint() {
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/session/recorded"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "value": null })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/sessions"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let endpoint = Url::parse(&server.uri()).unwrap();
+        let other_endpoint = RecordedSession {
+            endpoint: Url::parse("http://localhost:4444").unwrap(),
+            session_id: "other".to_string(),
+        };
+        record_session(
+            dir.path(),
+            &RecordedSession {
+                endpoint: endpoint.clone(),
+                session_id: "recorded".to_string(),
+            },
+        );
+        record_session(dir.path(), &other_endpoint);
+
+        assert_eq!(
+            cleanup_recorded_sessions(dir.path(), &endpoint)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(recorded_sessions(dir.path()), vec![other_endpoint]);
+    }
+
+    #[tokio::test]
+    async fn test_set_extra_headers_through_devtools() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/session"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "value": { "sessionId": "s1", "capabilities": { "browserName": "chrome" } }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/session/s1/goog/cdp/execute"))
+            .and(body_json(json!({ "cmd": "Network.enable", "params": {} })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "value": {} })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/session/s1/goog/cdp/execute"))
+            .and(body_json(json!({
+                "cmd": "Network.setExtraHTTPHeaders",
+                "params": { "headers": { "X-Corporate-Auth": "token" } }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "value": {} })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = fantoccini::ClientBuilder::native()
+            .connect(&server.uri())
+            .await
+            .unwrap();
+        let headers = BTreeMap::from([("X-Corporate-Auth".to_string(), "token".to_string())]);
+
+        set_extra_headers(&client, WebDriverBrowser::Chromium, &headers)
+            .await
+            .unwrap();
+        set_extra_headers(&client, WebDriverBrowser::Firefox, &headers)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_firefox_capabilities() {
+        let config = web_driver_config(r#"{"endpoint": "http://localhost:4444"}"#);
+
+        assert_eq!(
+            Value::Object(capabilities(&config, &NetworkConfig::default(), true)),
+            json!({
+                "browserName": "firefox",
+                "moz:firefoxOptions": { "args": ["-headless"] }
+            })
+        );
+        assert_eq!(
+            Value::Object(capabilities(&config, &NetworkConfig::default(), false)),
+            json!({ "browserName": "firefox" })
+        );
+    }
+
+    #[test]
+    fn test_chromium_capabilities_with_extra_capabilities() {
+        let config = web_driver_config(
+            r#"{
+                "endpoint": "http://localhost:9515",
+                "browser": "chromium",
+                "binary": "/usr/bin/chromium",
+                "user_data_dir": "/tmp/lanci-profile",
+                "args": ["--no-sandbox"],
+                "capabilities": {
+                    "goog:chromeOptions": { "args": ["--disable-gpu"] },
+                    "acceptInsecureCerts": true
+                }
+            }"#,
+        );
+
+        assert_eq!(
+            Value::Object(capabilities(&config, &NetworkConfig::default(), true)),
+            json!({
+                "browserName": "chrome",
+                "goog:chromeOptions": {
+                    "args": [
+                        "--headless=new",
+                        "--user-data-dir=/tmp/lanci-profile",
+                        "--no-sandbox",
+                        "--disable-gpu"
+                    ],
+                    "binary": "/usr/bin/chromium"
+                },
+                "acceptInsecureCerts": true
+            })
+        );
+    }
+
+    #[test]
+    fn test_network_capabilities() {
+        let network: NetworkConfig = serde_json::from_str(
+            r#"{
+                "proxy": "http://proxy.example.com:3128",
+                "timeout_secs": 30,
+                "user_agent": "lanci-test",
+                "ca_certificates": ["/etc/ssl/certs/corporate-root.pem"]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Value::Object(capabilities(
+                &web_driver_config(r#"{"browser": "chrome"}"#),
+                &network,
+                false
+            )),
+            json!({
+                "browserName": "chrome",
+                "goog:chromeOptions": { "args": ["--user-agent=lanci-test"] },
+                "proxy": {
+                    "proxyType": "manual",
+                    "httpProxy": "proxy.example.com:3128",
+                    "sslProxy": "proxy.example.com:3128"
+                },
+                "timeouts": { "pageLoad": 30000 },
+                "acceptInsecureCerts": true
+            })
+        );
+        assert_eq!(
+            proxy_capability(&Url::parse("socks5://localhost:1080").unwrap()),
+            json!({ "proxyType": "manual", "socksProxy": "localhost:1080", "socksVersion": 5 })
+        );
+    }
+}
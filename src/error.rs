@@ -1,3 +1,4 @@
+use serde::Deserialize;
 use std::{fmt, io};
 use thiserror::Error;
 
@@ -20,12 +21,51 @@ pub enum ConfigParseError {
 
     #[error("Missing required field in cookies: {0}")]
     CookieParseError(&'static str),
+
+    #[error("Failed to write configuration file: {0}")]
+    WriteFileError(io::Error),
+
+    #[error("Failed to import cookies from browser profile: {0}")]
+    BrowserCookieError(#[from] BrowserCookieError),
+
+    #[error("No profile named `{0}` in the configuration file")]
+    UnknownProfile(String),
+}
+
+#[derive(Error, Debug)]
+pub enum BrowserCookieError {
+    #[error("No Firefox or Chromium cookie database found at {0}")]
+    DatabaseNotFound(String),
+
+    #[error("Failed to copy browser cookie database: {0}")]
+    CopyError(#[from] io::Error),
+
+    #[error("Failed to read browser cookie database: {0}")]
+    SqliteError(#[from] rusqlite::Error),
+
+    #[error(
+        "LeetCode cookies in this Chromium profile are encrypted, only unencrypted cookie databases are supported"
+    )]
+    EncryptedCookies,
+
+    #[error(
+        "No LeetCode cookies found in {0}, sign in to LeetCode with this browser profile first"
+    )]
+    NoCookies(String),
+
+    #[error("Missing required cookie in browser profile: {0}")]
+    MissingCookie(&'static str),
+
+    #[error("{0}")]
+    Other(String),
 }
 
 #[derive(Error, Debug)]
 pub enum CrawlerError {
-    #[error("Rate limit can not be zero")]
-    ZeroRateLimit,
+    #[error(
+        "Rate limit must be a positive number of requests per second with a burst of at least 1"
+    )]
+    InvalidRateLimit,
 
     #[error("Failed to parse URL: {0}")]
     UrlParseError(#[from] url::ParseError),
@@ -33,6 +73,9 @@ pub enum CrawlerError {
     #[error("Failed to get slug from URL: {0}")]
     SlugParseError(String),
 
+    #[error("Invalid network configuration: {0}")]
+    NetworkConfigError(String),
+
     #[error("Failed to build reqwest client: {0}")]
     BuildReqwestClientError(reqwest::Error),
 
@@ -45,6 +88,54 @@ pub enum CrawlerError {
     #[error("Failed to execute web driver command: {0}")]
     WebDriverCommandError(#[from] fantoccini::error::CmdError),
 
+    #[error(
+        "LeetCode session is not valid: {0}. Refresh the cookies, e.g. with `lanci auth login`"
+    )]
+    InvalidSession(String),
+
+    #[error("LeetCode GraphQL returned errors: {}", join_graphql_errors(.0))]
+    GraphQlErrors(Vec<GraphQlError>),
+
+    #[error(
+        "LeetCode GraphQL returned HTTP {status} (content-type: {content_type}). Body starts with: {excerpt}"
+    )]
+    HttpStatus {
+        status: u16,
+        content_type: String,
+        excerpt: String,
+    },
+
+    #[error(
+        "LeetCode is rate limiting requests (HTTP 429), lower `rate_limit` and try again later"
+    )]
+    RateLimited,
+
+    #[error("Unexpected LeetCode response: {0}")]
+    UnexpectedResponse(String),
+
+    #[error("Not found on LeetCode: {0}")]
+    NotFound(String),
+
+    #[error("Problem `{0}` is premium-only and the session has no LeetCode Premium subscription")]
+    PremiumOnly(String),
+
+    #[error("Failed to save debug artifacts: {0}")]
+    DebugArtifactsError(io::Error),
+
+    #[error("Failed to start the WebDriver: {0}")]
+    WebDriverSpawnError(String),
+
+    #[error(
+        "LeetCode answered with a Cloudflare or captcha challenge ({0}). Run with a visible browser (`web_driver.headless: false`) to solve it"
+    )]
+    ChallengeRequired(String),
+
+    #[error("This operation requires a web driver, but the crawler was created without one")]
+    WebDriverUnavailable,
+
+    #[error("Timed out after {0} seconds waiting for the LeetCode sign-in to complete")]
+    LoginTimedOut(u64),
+
     #[error("Unexpected empty result when fetching `{0}`")]
     EmptyResult(&'static str),
 
@@ -52,6 +143,74 @@ pub enum CrawlerError {
     Other(String),
 }
 
+impl CrawlerError {
+    /// Returns `true` for errors that would fail every following request the same way, so a
+    /// batch should stop instead of skipping to the next problem.
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            CrawlerError::InvalidSession(_)
+                | CrawlerError::RateLimited
+                | CrawlerError::ChallengeRequired(_)
+        )
+    }
+}
+
+/// One entry of the `errors` array of a GraphQL response.
+#[derive(Debug, Deserialize)]
+pub struct GraphQlError {
+    pub message: String,
+    /// Path of the response field the error belongs to, e.g. `["question", "content"]`.
+    #[serde(default)]
+    pub path: Vec<serde_json::Value>,
+}
+
+impl fmt::Display for GraphQlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if !self.path.is_empty() {
+            let path = self
+                .path
+                .iter()
+                .map(|segment| match segment {
+                    serde_json::Value::String(segment) => segment.clone(),
+                    segment => segment.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(".");
+            write!(f, " (at {})", path)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn join_graphql_errors(errors: &[GraphQlError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[derive(Error, Debug)]
+pub enum CatalogError {
+    #[error("Failed to access problem catalog file: {0}")]
+    FileError(#[from] io::Error),
+
+    #[error("Failed to parse problem catalog file: {0}")]
+    SerdeError(#[from] serde_json::Error),
+
+    #[error("No problem matches `{0}`")]
+    ProblemNotFound(String),
+
+    #[error("`{query}` matches several problems: {candidates}")]
+    AmbiguousProblem { query: String, candidates: String },
+
+    #[error(transparent)]
+    CrawlerError(#[from] CrawlerError),
+}
+
 #[derive(Error, Debug)]
 pub enum AnkiError {
     #[error("Failed to load syntax highlighting theme: {0}")]
@@ -62,4 +221,11 @@ pub enum AnkiError {
     WriteDeckError(Box<genanki_rs::Error>),
     #[error("Deck filename is not valid UTF-8")]
     InvalidDeckFilename,
+    #[error("Failed to reach AnkiConnect, is Anki running with the AnkiConnect add-on? {0}")]
+    AnkiConnectRequestError(#[from] reqwest::Error),
+    #[error("AnkiConnect action `{action}` failed: {message}")]
+    AnkiConnectError {
+        action: &'static str,
+        message: String,
+    },
 }
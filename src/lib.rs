@@ -1,7 +1,12 @@
 pub mod anki;
+pub mod anki_connect;
+pub mod auth;
+pub mod catalog;
 pub mod cli;
 pub mod config;
 pub mod crawler;
 pub mod error;
 pub mod html;
+pub mod logging;
 pub mod markdown;
+pub mod metrics;
@@ -1,19 +1,114 @@
+use comrak::plugins::syntect::SyntectAdapter;
 use lanci::anki::{AnkiDeckManager, set_up_comrak_syntect_adapter};
-use lanci::cli::{self, Cli};
-use lanci::config::Config;
-use lanci::crawler::leetcode::{LeetCodeCrawler, extract_slug_from_url};
+use lanci::anki_connect::{AnkiConnectClient, PushOutcome};
+use lanci::auth::browser::import_cookies;
+use lanci::auth::credentials::Credentials;
+use lanci::auth::login::login_with_web_driver;
+use lanci::auth::session::{EXPIRY_WARNING_THRESHOLD, remaining_validity, session_expiry};
+use lanci::catalog::{Catalog, ProblemResolver};
+use lanci::cli::{
+    self, AuthCommand, CatalogCommand, CatalogSearchArgs, Cli, Command, ContestArgs, DailyArgs,
+    FetchArgs, LogFormat, LoginArgs, WebDriverCommand,
+};
+use lanci::config::{Config, ConfigFile};
+use lanci::crawler::leetcode::contest::ContestDetail;
+use lanci::crawler::leetcode::daily::ChallengeDate;
+use lanci::crawler::leetcode::problemset::{QuestionListFilter, QuestionStatus};
+use lanci::crawler::leetcode::submission::SubmissionMeta;
+use lanci::crawler::leetcode::{LeetCodeCrawler, LeetCodeProblem, check_session_expiry};
+use lanci::crawler::web_driver::{WebDriverProcess, cleanup_sessions};
+use lanci::error::{CatalogError, CrawlerError};
+use lanci::logging::RedactingMakeWriter;
 use lanci::markdown::{ToMarkdown, save_markdown_to_file};
-use tracing::{error, info};
-use tracing_subscriber::EnvFilter;
+use lanci::metrics::{self, MetricsLayer, RunReport};
+use std::fs::OpenOptions;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{Level, debug, error, info, warn};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+const DEBUG_ARTIFACTS_DIR: &str = "debug";
 
 #[tokio::main]
 async fn main() {
     let cli_args = cli::parse_args();
 
-    init_tracing_subscriber();
-    if let Err(e) = run(&cli_args).await {
+    let metrics = match init_tracing_subscriber(&cli_args) {
+        Ok(metrics) => metrics,
+        Err(e) => {
+            eprintln!("Failed to open log file: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // Dropping `run` on a signal deletes the open browser sessions and stops a WebDriver process
+    // started by lanci
+    let result = tokio::select! {
+        result = run(&cli_args) => result,
+        signal = shutdown_signal() => Err(anyhow::anyhow!("Interrupted by {}", signal)),
+    };
+    if matches!(
+        cli_args.command,
+        Command::Fetch(_) | Command::Contest(_) | Command::Daily(_)
+    ) {
+        report_run(&cli_args, &metrics.report());
+    }
+    if let Err(e) = result {
         error!("{}", e);
-        std::process::exit(1);
+        std::process::exit(exit_code(&e));
+    }
+}
+
+/// Waits for Ctrl-C, or SIGTERM on Unix, and returns the signal name.
+async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => tokio::select! {
+                _ = tokio::signal::ctrl_c() => "Ctrl-C",
+                _ = terminate.recv() => "SIGTERM",
+            },
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+                "Ctrl-C"
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "Ctrl-C"
+    }
+}
+
+/// Maps an error to the process exit code documented in the README, so that scripts can tell the
+/// causes of a failure apart.
+fn exit_code(error: &anyhow::Error) -> i32 {
+    let crawler_error = match error.downcast_ref() {
+        Some(CatalogError::ProblemNotFound(_)) => return 4,
+        Some(CatalogError::CrawlerError(e)) => Some(e),
+        _ => error.downcast_ref::<CrawlerError>(),
+    };
+
+    match crawler_error {
+        Some(CrawlerError::InvalidSession(_)) => 3,
+        Some(CrawlerError::NotFound(_)) => 4,
+        Some(CrawlerError::PremiumOnly(_)) => 5,
+        Some(CrawlerError::RateLimited) => 6,
+        Some(CrawlerError::ChallengeRequired(_)) => 7,
+        Some(
+            CrawlerError::GraphQlErrors(_)
+            | CrawlerError::HttpStatus { .. }
+            | CrawlerError::UnexpectedResponse(_),
+        ) => 8,
+        _ => 1,
     }
 }
 
@@ -22,39 +117,766 @@ async fn run(cli_args: &Cli) -> anyhow::Result<()> {
         .config
         .to_str()
         .ok_or_else(|| anyhow::anyhow!("Invalid config file path"))?;
-    let config = Config::load_from_file(config_path)
+    let merges_accounts = matches!(
+        cli_args.command,
+        Command::Fetch(_) | Command::Contest(_) | Command::Daily(_)
+    );
+    if cli_args.profiles.len() > 1 && !merges_accounts {
+        anyhow::bail!("Only fetch, contest and daily accept several profiles");
+    }
+    // The same browser cookie for every profile would crawl one account several times
+    if cli_args.profiles.len() > 1 && cli_args.browser_profile.is_some() {
+        anyhow::bail!(
+            "--browser-profile cannot be combined with several profiles, set browser_profile in each profile instead"
+        );
+    }
+    // Signing in must work before a valid cookie is configured
+    if let Command::Auth(AuthCommand::Login(login_args)) = &cli_args.command {
+        let profile = cli_args.profiles.first().map(String::as_str);
+        return login(config_path, profile, login_args).await;
+    }
+
+    let mut configs = load_configs(config_path, &cli_args.profiles).await?;
+    if let Some(profile_path) = &cli_args.browser_profile {
+        let config = &mut configs[0];
+        config.cookie = Some(import_cookies(profile_path, None, config.site)?);
+    }
+    if cli_args.anonymous {
+        for config in &mut configs {
+            config.cookie = None;
+        }
+    }
+    if cli_args.debug_artifacts {
+        for config in &mut configs {
+            config.scraping.debug_artifacts_dir = Some(PathBuf::from(DEBUG_ARTIFACTS_DIR));
+        }
+    }
+    // Only crawling with a session needs a browser. Profiles cannot set `web_driver`, so one
+    // driver serves all of them
+    let needs_web_driver = merges_accounts && configs.iter().any(|config| config.cookie.is_some());
+    let web_driver_process = match &configs[0].web_driver.driver {
+        Some(driver) if needs_web_driver => {
+            let process = WebDriverProcess::spawn(driver).await?;
+            for config in &mut configs {
+                config.web_driver.endpoint = process.endpoint().clone();
+            }
+            Some(process)
+        }
+        _ => None,
+    };
+
+    let result = run_command(cli_args, &configs).await;
+    if let Some(process) = web_driver_process {
+        process.shutdown().await?;
+    }
+
+    result
+}
+
+async fn run_command(cli_args: &Cli, configs: &[Config]) -> anyhow::Result<()> {
+    let (config, other_accounts) = configs
+        .split_first()
+        .expect("at least one configuration is loaded");
+
+    match &cli_args.command {
+        Command::Fetch(fetch_args) => fetch(cli_args, config, other_accounts, fetch_args).await,
+        Command::Contest(contest_args) => {
+            contest(cli_args, config, other_accounts, contest_args).await
+        }
+        Command::Daily(daily_args) => daily(cli_args, config, other_accounts, daily_args).await,
+        Command::Catalog(CatalogCommand::Update) => update_catalog(config).await,
+        Command::Catalog(CatalogCommand::Search(search_args)) => {
+            search_catalog(config, search_args).await
+        }
+        Command::Auth(AuthCommand::Status) => auth_status(config).await,
+        Command::WebDriver(WebDriverCommand::Cleanup) => web_driver_cleanup(config).await,
+        Command::Auth(AuthCommand::Login(_)) => unreachable!("handled before loading the config"),
+    }
+}
+
+/// Loads the configuration once for each selected profile, or once without a profile.
+async fn load_configs(config_path: &str, profiles: &[String]) -> anyhow::Result<Vec<Config>> {
+    let load_error = |e| anyhow::anyhow!("Failed to load config file: {}", e);
+    if profiles.is_empty() {
+        return Ok(vec![
+            Config::load_from_file(config_path)
+                .await
+                .map_err(load_error)?,
+        ]);
+    }
+
+    let mut configs = Vec::with_capacity(profiles.len());
+    for profile in profiles {
+        configs.push(
+            Config::load_profile_from_file(config_path, profile)
+                .await
+                .map_err(load_error)?,
+        );
+    }
+
+    Ok(configs)
+}
+
+async fn fetch(
+    cli_args: &Cli,
+    config: &Config,
+    other_accounts: &[Config],
+    fetch_args: &FetchArgs,
+) -> anyhow::Result<()> {
+    let crawler = new_crawler(config).await?;
+
+    let crawl_result = crawl_requested(&crawler, config, fetch_args).await;
+    save_refreshed_cookies(config, &crawler);
+    crawler.close().await?;
+    let (mut problems, deck_name) = crawl_result?;
+    merge_account_submissions(other_accounts, &mut problems, |_| true).await?;
+
+    export_problems(cli_args, config, &problems, &deck_name).await
+}
+
+async fn login(
+    config_path: &str,
+    profile: Option<&str>,
+    login_args: &LoginArgs,
+) -> anyhow::Result<()> {
+    let mut config_file = ConfigFile::load_from_file(config_path)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to load config file: {}", e))?;
+    if let Some(profile) = profile {
+        config_file = config_file.select_profile(profile)?;
+    }
+    let web_driver_process = match &config_file.web_driver.driver {
+        Some(driver) => {
+            let process = WebDriverProcess::spawn(driver).await?;
+            config_file.web_driver.endpoint = process.endpoint().clone();
+            Some(process)
+        }
+        None => None,
+    };
+
+    let login_result = login_with_web_driver(
+        &config_file.web_driver,
+        &config_file.network,
+        config_file.site,
+        Duration::from_secs(login_args.timeout),
+    )
+    .await;
+    if let Some(process) = web_driver_process {
+        process.shutdown().await?;
+    }
+    let cookie = login_result?;
+    let crawler = LeetCodeCrawler::without_web_driver(
+        &config_file.rate_limits(),
+        config_file.site,
+        &config_file.network,
+        &cookie,
+    )?;
+    let username = crawler.ensure_signed_in().await?;
+
+    match &config_file.credentials_file {
+        Some(credentials_file) => {
+            let mut credentials = if tokio::fs::try_exists(credentials_file).await? {
+                Credentials::load_from_file(credentials_file)?
+            } else {
+                Credentials::default()
+            };
+            credentials.cookie = Some(cookie.to_string());
+            credentials.save_to_file(credentials_file)?;
+            info!(
+                "Signed in as {}, cookies saved to {}",
+                username,
+                credentials_file.display()
+            );
+        }
+        None => {
+            ConfigFile::save_cookie(config_path, profile, &cookie).await?;
+            info!(
+                "Signed in as {}, cookies saved to {}",
+                username, config_path
+            );
+        }
+    }
+    if config_file.browser_profile.is_some() || config_file.cookie_file.is_some() {
+        warn!("Another cookie source is configured and takes precedence over the saved cookie.");
+    }
+
+    Ok(())
+}
+
+async fn auth_status(config: &Config) -> anyhow::Result<()> {
+    let cookie = config
+        .cookie
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("No LeetCode cookie is configured"))?;
 
-    let crawler =
-        LeetCodeCrawler::new(config.rate_limit, &config.web_driver, &config.cookie).await?;
-    let slug = extract_slug_from_url(&cli_args.url)?;
+    check_session_expiry(cookie)?;
+    let crawler = LeetCodeCrawler::without_web_driver(
+        &config.rate_limits,
+        config.site,
+        &config.network,
+        cookie,
+    )?;
+    let username = crawler.ensure_signed_in().await?;
+    save_refreshed_cookies(config, &crawler);
+    println!("Signed in as {}", username);
 
-    info!("Crawling problem with slug: {}", slug);
-    let crawl_result = crawler.crawl_problem(slug).await;
+    match session_expiry(&cookie.leet_code_token).and_then(remaining_validity) {
+        Some(remaining) => {
+            let hours = remaining.as_secs() / 3600;
+            println!(
+                "Session expires in {} days {} hours",
+                hours / 24,
+                hours % 24
+            );
+            if remaining < EXPIRY_WARNING_THRESHOLD {
+                warn!("The LeetCode session expires soon, refresh it with `lanci auth login`.");
+            }
+        }
+        None => println!("Session expiry is unknown"),
+    }
+
+    Ok(())
+}
+
+async fn web_driver_cleanup(config: &Config) -> anyhow::Result<()> {
+    if config.web_driver.driver.is_some() {
+        info!(
+            "web_driver.driver is set, lanci stops the driver it starts along with its sessions."
+        );
+    }
+    let endpoint = &config.web_driver.endpoint;
+    let deleted = cleanup_sessions(endpoint).await?;
+    println!(
+        "Deleted {} stale WebDriver session(s) on {}",
+        deleted, endpoint
+    );
+
+    Ok(())
+}
+
+/// Writes the session cookies back to the credentials file when LeetCode has refreshed them and
+/// `persist_refreshed_cookies` is enabled. Failures only produce a warning.
+fn save_refreshed_cookies(config: &Config, crawler: &LeetCodeCrawler) {
+    if !config.persist_refreshed_cookies {
+        return;
+    }
+    let (Some(cookie), Some(refreshed)) = (&config.cookie, crawler.current_cookies()) else {
+        return;
+    };
+    if refreshed.csrf_token == cookie.csrf_token
+        && refreshed.leet_code_token == cookie.leet_code_token
+    {
+        return;
+    }
+    let Some(credentials_file) = &config.credentials_file else {
+        warn!(
+            "persist_refreshed_cookies requires credentials_file, the refreshed cookies were not saved."
+        );
+        return;
+    };
+
+    let mut credentials = if credentials_file.exists() {
+        match Credentials::load_from_file(credentials_file) {
+            Ok(credentials) => credentials,
+            Err(e) => {
+                warn!("Failed to save refreshed cookies: {}", e);
+                return;
+            }
+        }
+    } else {
+        Credentials::default()
+    };
+    credentials.cookie = Some(refreshed.to_string());
+
+    match credentials.save_to_file(credentials_file) {
+        Ok(()) => info!("Saved refreshed cookies to {}", credentials_file.display()),
+        Err(e) => warn!("Failed to save refreshed cookies: {}", e),
+    }
+}
+
+/// Creates a crawler for the configured session, or an anonymous one when no cookie is configured.
+async fn new_crawler(config: &Config) -> anyhow::Result<LeetCodeCrawler> {
+    let mut crawler = match &config.cookie {
+        Some(cookie) => {
+            LeetCodeCrawler::new(
+                &config.rate_limits,
+                config.site,
+                &config.web_driver,
+                &config.scraping,
+                &config.network,
+                cookie,
+            )
+            .await?
+        }
+        None => {
+            info!("No LeetCode cookie configured, running in anonymous mode without submissions.");
+            LeetCodeCrawler::anonymous(&config.rate_limits, config.site, &config.network)?
+        }
+    };
+    crawler.set_premium_only_policy(config.premium_only);
+
+    Ok(crawler)
+}
+
+async fn contest(
+    cli_args: &Cli,
+    config: &Config,
+    other_accounts: &[Config],
+    contest_args: &ContestArgs,
+) -> anyhow::Result<()> {
+    let crawler = new_crawler(config).await?;
+
+    let crawl_result = crawl_contest(&crawler, &contest_args.slug).await;
+    save_refreshed_cookies(config, &crawler);
     crawler.close().await?;
-    let problem = crawl_result?;
+    let (mut problems, contest) = crawl_result?;
+    let (start, end) = (contest.start_time, contest.end_time());
+    merge_account_submissions(other_accounts, &mut problems, |meta| {
+        meta.submitted_between(start, end)
+    })
+    .await?;
+
+    export_problems(cli_args, config, &problems, &contest.title).await
+}
 
-    let markdown = problem.to_markdown()?;
-    let md_filename = cli_args.output_dir.join(format!("{}.md", problem.name));
+async fn daily(
+    cli_args: &Cli,
+    config: &Config,
+    other_accounts: &[Config],
+    daily_args: &DailyArgs,
+) -> anyhow::Result<()> {
+    // Checked before the crawler starts, so that scheduled runs are idempotent and cheap
+    let date = daily_args.date.unwrap_or_else(ChallengeDate::today);
+    let marker = daily_marker_path(cli_args, &date.to_string());
+    if !daily_args.force && tokio::fs::try_exists(&marker).await? {
+        info!(
+            "Daily challenge for {} already exported ({} exists), skipping.",
+            date,
+            marker.display()
+        );
+        return Ok(());
+    }
+
+    let crawler = new_crawler(config).await?;
+
+    let crawl_result = crawl_daily(&crawler, daily_args).await;
+    save_refreshed_cookies(config, &crawler);
+    crawler.close().await?;
+
+    let (mut problems, challenge_date) = crawl_result?;
+    merge_account_submissions(other_accounts, &mut problems, |_| true).await?;
+    export_problems(
+        cli_args,
+        config,
+        &problems,
+        &format!("daily-{}", challenge_date),
+    )
+    .await?;
+
+    // Written after the deck file or the AnkiConnect push, whichever was used
+    let marker = daily_marker_path(cli_args, &challenge_date);
+    tokio::fs::write(&marker, &problems[0].description.question_title_slug).await?;
+    debug!("Recorded daily challenge export in {}", marker.display());
+
+    Ok(())
+}
+
+/// File recording that the daily challenge of `date` was exported.
+fn daily_marker_path(cli_args: &Cli, date: &str) -> PathBuf {
+    cli_args.output_dir.join(format!("daily-{}.done", date))
+}
+
+/// Adds the accepted submissions of other accounts to already crawled problems, skipping code that
+/// is already there. Accounts are crawled one after the other, each in its own browser session.
+async fn merge_account_submissions(
+    accounts: &[Config],
+    problems: &mut [LeetCodeProblem],
+    filter: impl Fn(&SubmissionMeta) -> bool + Copy,
+) -> anyhow::Result<()> {
+    for account in accounts {
+        let profile = account.profile.as_deref().unwrap_or("default");
+        if account.cookie.is_none() {
+            warn!(
+                "Profile {} has no cookie, skipping its submissions",
+                profile
+            );
+            continue;
+        }
+
+        info!("Merging submissions of profile {}", profile);
+        let crawler = new_crawler(account).await?;
+        let merge_result = merge_submissions(&crawler, problems, filter).await;
+        save_refreshed_cookies(account, &crawler);
+        crawler.close().await?;
+        merge_result?;
+    }
+
+    Ok(())
+}
+
+async fn merge_submissions(
+    crawler: &LeetCodeCrawler,
+    problems: &mut [LeetCodeProblem],
+    filter: impl Fn(&SubmissionMeta) -> bool + Copy,
+) -> anyhow::Result<()> {
+    for problem in problems {
+        let slug = &problem.description.question_title_slug;
+        let submissions = match crawler
+            .fetch_accepted_submissions_matching(slug, filter)
+            .await
+        {
+            Ok(submissions) => submissions,
+            Err(e) if e.is_fatal() => return Err(e.into()),
+            Err(e) => {
+                warn!("Skipping submissions of problem {}: {}", slug, e);
+                continue;
+            }
+        };
+
+        for submission in submissions {
+            if !problem.submissions.contains(&submission) {
+                problem.submissions.push(submission);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Crawls the daily challenge and returns it with its date.
+async fn crawl_daily(
+    crawler: &LeetCodeCrawler,
+    daily_args: &DailyArgs,
+) -> anyhow::Result<(Vec<LeetCodeProblem>, String)> {
+    let challenge = crawler.fetch_daily_challenge(daily_args.date).await?;
+
+    info!(
+        "Crawling daily challenge for {} with slug: {}",
+        challenge.date, challenge.question.title_slug
+    );
+    let mut problem = crawler
+        .crawl_problem(&challenge.question.title_slug)
+        .await?;
+    problem.extra_tags.push("daily-challenge".to_string());
+
+    Ok((vec![problem], challenge.date))
+}
+
+/// Crawls every problem of a contest, keeping only the submissions made while the contest was running.
+async fn crawl_contest(
+    crawler: &LeetCodeCrawler,
+    contest_slug: &str,
+) -> anyhow::Result<(Vec<LeetCodeProblem>, ContestDetail)> {
+    let contest = crawler.fetch_contest(contest_slug).await?;
+    let (start, end) = (contest.contest.start_time, contest.contest.end_time());
+    let mut problems = Vec::with_capacity(contest.contest_question_list.len());
+    let mut first_error = None;
+
+    for (i, question) in contest.contest_question_list.iter().enumerate() {
+        info!(
+            "Crawling contest problem {}/{} with slug: {}",
+            i + 1,
+            contest.contest_question_list.len(),
+            question.title_slug
+        );
+        match crawler
+            .crawl_problem_matching(&question.title_slug, |meta| {
+                meta.submitted_between(start, end)
+            })
+            .await
+        {
+            Ok(mut problem) => {
+                problem.extra_tags.push(contest.contest.tag());
+                problems.push(problem);
+            }
+            Err(e) if e.is_fatal() => return Err(e.into()),
+            Err(e) => {
+                warn!("Skipping problem {}: {}", question.title_slug, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    match first_error {
+        Some(e) if problems.is_empty() => Err(e.into()),
+        _ => Ok((problems, contest.contest)),
+    }
+}
+
+/// Crawls the problems selected by the fetch arguments and returns them with the deck name to use.
+async fn crawl_requested(
+    crawler: &LeetCodeCrawler,
+    config: &Config,
+    fetch_args: &FetchArgs,
+) -> anyhow::Result<(Vec<LeetCodeProblem>, String)> {
+    if let [query] = fetch_args.problems.as_slice() {
+        let slug = ProblemResolver::new(crawler, &config.catalog_path)
+            .resolve(query)
+            .await?;
+        info!("Crawling problem with slug: {}", slug);
+        let problem = crawler.crawl_problem(&slug).await?;
+        let deck_name = problem.name.clone();
+
+        return Ok((vec![problem], deck_name));
+    }
+
+    if !fetch_args.problems.is_empty() {
+        let mut resolver = ProblemResolver::new(crawler, &config.catalog_path);
+        let mut slugs = Vec::with_capacity(fetch_args.problems.len());
+        for query in &fetch_args.problems {
+            slugs.push(resolver.resolve(query).await?);
+        }
+
+        return Ok((
+            crawl_problems(crawler, &slugs).await?,
+            config.anki.deck.name.clone(),
+        ));
+    }
+
+    let tag = fetch_args
+        .tag
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("Either a problem or --tag is required"))?;
+    let filter = QuestionListFilter {
+        tags: vec![tag.to_string()],
+        difficulty: fetch_args.difficulty.map(Into::into),
+        status: fetch_args.status.map(Into::into),
+    };
+    let slugs = crawler
+        .fetch_problemset_questions(&filter)
+        .await?
+        .into_iter()
+        .map(|question| question.title_slug)
+        .collect::<Vec<_>>();
+
+    Ok((crawl_problems(crawler, &slugs).await?, tag.to_string()))
+}
+
+async fn update_catalog(config: &Config) -> anyhow::Result<()> {
+    let crawler = match &config.cookie {
+        Some(cookie) => LeetCodeCrawler::without_web_driver(
+            &config.rate_limits,
+            config.site,
+            &config.network,
+            cookie,
+        )?,
+        None => LeetCodeCrawler::anonymous(&config.rate_limits, config.site, &config.network)?,
+    };
+    let catalog = Catalog::download(&crawler, &config.catalog_path).await;
+    save_refreshed_cookies(config, &crawler);
+    let catalog = catalog?;
+    info!(
+        "Saved {} problems to {}",
+        catalog.questions.len(),
+        config.catalog_path.display()
+    );
+
+    Ok(())
+}
+
+async fn search_catalog(config: &Config, search_args: &CatalogSearchArgs) -> anyhow::Result<()> {
+    let catalog = Catalog::load_from_file(&config.catalog_path)
+        .await
+        .map_err(|e| anyhow::anyhow!("{} (run `lanci catalog update` first)", e))?;
+    let filter = QuestionListFilter {
+        tags: search_args.tag.iter().cloned().collect(),
+        difficulty: search_args.difficulty.map(Into::into),
+        status: search_args.status.map(Into::into),
+    };
+
+    let results = catalog
+        .search(search_args.query.as_deref(), &filter)
+        .into_iter()
+        .filter(|question| !(search_args.free_only && question.is_paid_only))
+        .collect::<Vec<_>>();
+
+    for question in &results {
+        println!(
+            "{:>5}  {:<60}  {:<7}  {:>5.1}%  {:<9}{}",
+            question.frontend_question_id,
+            question.title,
+            question.difficulty.to_string(),
+            question.ac_rate,
+            question.status().to_string(),
+            if question.is_paid_only {
+                "  premium"
+            } else {
+                ""
+            }
+        );
+    }
+
+    let count = |status| {
+        results
+            .iter()
+            .filter(|question| question.status() == status)
+            .count()
+    };
+    println!(
+        "{} problems: {} solved, {} attempted, {} todo",
+        results.len(),
+        count(QuestionStatus::Solved),
+        count(QuestionStatus::Attempted),
+        count(QuestionStatus::Todo)
+    );
+
+    Ok(())
+}
+
+/// Crawls each slug in order, skipping problems that fail so one bad problem does not abort a batch.
+/// Fatal errors such as an invalid session still abort, since every remaining problem would fail
+/// the same way. When every problem fails, the first error is returned.
+async fn crawl_problems(
+    crawler: &LeetCodeCrawler,
+    slugs: &[String],
+) -> anyhow::Result<Vec<LeetCodeProblem>> {
+    let mut problems = Vec::with_capacity(slugs.len());
+    let mut first_error = None;
+
+    for (i, slug) in slugs.iter().enumerate() {
+        info!(
+            "Crawling problem {}/{} with slug: {}",
+            i + 1,
+            slugs.len(),
+            slug
+        );
+        match crawler.crawl_problem(slug).await {
+            Ok(problem) => problems.push(problem),
+            Err(e) if e.is_fatal() => return Err(e.into()),
+            Err(e) => {
+                warn!("Skipping problem {}: {}", slug, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    match first_error {
+        Some(e) if problems.is_empty() => Err(e.into()),
+        _ => Ok(problems),
+    }
+}
+
+/// Saves each problem as a Markdown file and bundles all of them into a single Anki deck, or pushes
+/// them into a running Anki with `--anki-connect`.
+async fn export_problems(
+    cli_args: &Cli,
+    config: &Config,
+    problems: &[LeetCodeProblem],
+    deck_name: &str,
+) -> anyhow::Result<()> {
+    if problems.is_empty() {
+        anyhow::bail!("No problems to export");
+    }
 
     tokio::fs::create_dir_all(&cli_args.output_dir).await?;
-    save_markdown_to_file(md_filename, &markdown).await?;
-    info!("Problem saved to markdown successfully.");
+    for problem in problems {
+        let markdown = problem.to_markdown()?;
+        let md_filename = cli_args.output_dir.join(format!("{}.md", problem.name));
+        save_markdown_to_file(md_filename, &markdown).await?;
+        metrics::count(metrics::FILES_WRITTEN);
+    }
+    info!(
+        "{} problem(s) saved to markdown successfully.",
+        problems.len()
+    );
 
-    info!("Creating Anki deck for problem: {}", problem.name);
     // Load syntax highlighting theme
     let syntect_adapter = set_up_comrak_syntect_adapter()?;
+    if cli_args.anki_connect {
+        return push_problems(config, problems, &syntect_adapter).await;
+    }
+
+    info!("Creating Anki deck: {}", deck_name);
     let mut deck = AnkiDeckManager::new(&config.anki, &syntect_adapter)?;
-    deck.add_problem(&problem)?;
+    for problem in problems {
+        deck.add_problem(problem)?;
+    }
 
-    let deck_filename = cli_args.output_dir.join(format!("{}.apkg", problem.name));
+    let deck_filename = cli_args.output_dir.join(format!("{}.apkg", deck_name));
     deck.write_to_file(deck_filename)?;
+    metrics::count(metrics::FILES_WRITTEN);
+
+    Ok(())
+}
+
+/// Adds or updates the notes of the problems in a running Anki through AnkiConnect.
+async fn push_problems(
+    config: &Config,
+    problems: &[LeetCodeProblem],
+    syntect_adapter: &SyntectAdapter,
+) -> anyhow::Result<()> {
+    info!(
+        "Pushing {} problem(s) to Anki at {}",
+        problems.len(),
+        config.anki.connect.endpoint
+    );
+    let anki_connect = AnkiConnectClient::new(&config.anki, syntect_adapter);
+    anki_connect.prepare().await?;
+
+    let (mut added, mut updated, mut unchanged) = (0, 0, 0);
+    for problem in problems {
+        match anki_connect.push_problem(problem).await? {
+            PushOutcome::Added => added += 1,
+            PushOutcome::Updated => updated += 1,
+            PushOutcome::Unchanged => unchanged += 1,
+        }
+        debug!("Pushed {} to Anki", problem.name);
+    }
+    info!(
+        "{} note(s) added, {} updated and {} unchanged in Anki.",
+        added, updated, unchanged
+    );
 
     Ok(())
 }
 
-fn init_tracing_subscriber() {
+/// Writes the run summary as JSON to the `--report` file, or prints it as a table to standard
+/// error when that is a terminal, so that cron jobs and pipes get no extra output.
+fn report_run(cli_args: &Cli, report: &RunReport) {
+    let Some(report_path) = &cli_args.report else {
+        if std::io::stderr().is_terminal() {
+            eprint!("{}", report);
+        }
+        return;
+    };
+
+    let result = serde_json::to_string_pretty(report)
+        .map_err(std::io::Error::from)
+        .and_then(|json| std::fs::write(report_path, json));
+    match result {
+        Ok(()) => info!("Run report written to {}", report_path.display()),
+        Err(e) => warn!("Failed to write run report: {}", e),
+    }
+}
+
+/// Sets up logging to stdout or the `--log-file`, in text or JSON. Cookie, CSRF and session values
+/// are redacted from every event. Returns the layer collecting the run summary.
+fn init_tracing_subscriber(cli_args: &Cli) -> std::io::Result<MetricsLayer> {
     let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-    tracing_subscriber::fmt().with_env_filter(env_filter).init();
+    let (writer, ansi) = match &cli_args.log_file {
+        Some(log_file) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(log_file)?;
+            (BoxMakeWriter::new(Mutex::new(file)), false)
+        }
+        None => (BoxMakeWriter::new(std::io::stdout), true),
+    };
+    let fmt_layer = tracing_subscriber::fmt::layer()
+        .with_ansi(ansi)
+        .with_writer(RedactingMakeWriter::new(writer));
+    let fmt_layer = match cli_args.log_format {
+        LogFormat::Text => fmt_layer.boxed(),
+        LogFormat::Json => fmt_layer.json().boxed(),
+    };
+    // The metrics need lanci's debug-level spans whatever the log level is
+    let metrics = MetricsLayer::new();
+    let metrics_filter = Targets::new().with_target("lanci", Level::DEBUG);
+    tracing_subscriber::registry()
+        .with(fmt_layer.with_filter(env_filter))
+        .with(metrics.clone().with_filter(metrics_filter))
+        .init();
+
+    Ok(metrics)
 }
@@ -10,7 +10,7 @@ pub trait ToMarkdown {
 }
 
 /// Represents a Markdown code block with a specified language and code content
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct MarkdownCodeBlock {
     pub language: String,
     pub code: String,
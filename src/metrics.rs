@@ -0,0 +1,286 @@
+use serde::Serialize;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Span around crawling one problem.
+pub const CRAWL_PROBLEM_SPAN: &str = "crawl_problem";
+/// Span around one GraphQL request, including the rate limit wait.
+pub const POST_GRAPHQL_SPAN: &str = "post_graphql";
+/// Span around loading one submission page in the browser, including the rate limit wait.
+pub const FETCH_SUBMITTED_CODE_SPAN: &str = "fetch_submitted_code";
+
+pub const SUBMISSIONS_FETCHED: &str = "submissions_fetched";
+pub const CACHE_HITS: &str = "cache_hits";
+pub const RETRIES: &str = "retries";
+pub const FILES_WRITTEN: &str = "files_written";
+
+/// Counts one occurrence of a counter such as `RETRIES` in the run report.
+pub fn count(counter: &'static str) {
+    tracing::debug!(counter, "Counted {}", counter);
+}
+
+/// Summary of a run, built from the spans and counters above.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct RunReport {
+    pub problems_processed: u64,
+    pub submissions_fetched: u64,
+    pub cache_hits: u64,
+    pub retries: u64,
+    pub graphql_requests: u64,
+    /// Wall-clock seconds during which at least one GraphQL request was in flight.
+    pub graphql_seconds: f64,
+    pub page_loads: u64,
+    /// Wall-clock seconds during which at least one submission page was loading.
+    pub web_driver_seconds: f64,
+    pub files_written: u64,
+    pub total_seconds: f64,
+}
+
+impl fmt::Display for RunReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = [
+            ("Problems processed", self.problems_processed.to_string()),
+            ("Submissions fetched", self.submissions_fetched.to_string()),
+            ("Cache hits", self.cache_hits.to_string()),
+            ("Retries", self.retries.to_string()),
+            (
+                "GraphQL requests",
+                format!("{} ({:.1}s)", self.graphql_requests, self.graphql_seconds),
+            ),
+            (
+                "Browser page loads",
+                format!("{} ({:.1}s)", self.page_loads, self.web_driver_seconds),
+            ),
+            ("Files written", self.files_written.to_string()),
+            ("Total time", format!("{:.1}s", self.total_seconds)),
+        ];
+
+        writeln!(f, "Run summary")?;
+        for (label, value) in rows {
+            writeln!(f, "  {:<22}{:>12}", label, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A tracing layer that collects the `RunReport`. It must not be filtered by log level, since the
+/// spans and counters it relies on are recorded at debug level.
+#[derive(Clone)]
+pub struct MetricsLayer {
+    state: Arc<Mutex<MetricsState>>,
+    started: Instant,
+}
+
+#[derive(Default)]
+struct MetricsState {
+    report: RunReport,
+    graphql: BusyTime,
+    web_driver: BusyTime,
+}
+
+/// Wall-clock time during which at least one span of a kind was open. Concurrent spans are counted
+/// once, so that the time never exceeds the run time.
+#[derive(Default)]
+struct BusyTime {
+    open_spans: usize,
+    busy_since: Option<Instant>,
+    total: Duration,
+}
+
+impl BusyTime {
+    fn open(&mut self) {
+        if self.open_spans == 0 {
+            self.busy_since = Some(Instant::now());
+        }
+        self.open_spans += 1;
+    }
+
+    fn close(&mut self) {
+        self.open_spans = self.open_spans.saturating_sub(1);
+        if self.open_spans == 0
+            && let Some(busy_since) = self.busy_since.take()
+        {
+            self.total += busy_since.elapsed();
+        }
+    }
+
+    fn seconds(&self) -> f64 {
+        let current = self
+            .busy_since
+            .map_or(Duration::ZERO, |busy_since| busy_since.elapsed());
+
+        (self.total + current).as_secs_f64()
+    }
+}
+
+impl MetricsLayer {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MetricsState::default())),
+            started: Instant::now(),
+        }
+    }
+
+    /// Returns the report of everything recorded so far.
+    pub fn report(&self) -> RunReport {
+        let state = self.state.lock().unwrap();
+        let mut report = state.report.clone();
+        report.graphql_seconds = state.graphql.seconds();
+        report.web_driver_seconds = state.web_driver.seconds();
+        report.total_seconds = self.started.elapsed().as_secs_f64();
+
+        report
+    }
+
+    fn open_span(&self, name: &str) {
+        let mut state = self.state.lock().unwrap();
+        match name {
+            POST_GRAPHQL_SPAN => state.graphql.open(),
+            FETCH_SUBMITTED_CODE_SPAN => state.web_driver.open(),
+            _ => {}
+        }
+    }
+
+    fn close_span(&self, name: &str) {
+        let mut state = self.state.lock().unwrap();
+        match name {
+            CRAWL_PROBLEM_SPAN => state.report.problems_processed += 1,
+            POST_GRAPHQL_SPAN => {
+                state.report.graphql_requests += 1;
+                state.graphql.close();
+            }
+            FETCH_SUBMITTED_CODE_SPAN => {
+                state.report.page_loads += 1;
+                state.web_driver.close();
+            }
+            _ => {}
+        }
+    }
+
+    fn record_counter(&self, counter: &str) {
+        let report = &mut self.state.lock().unwrap().report;
+        match counter {
+            SUBMISSIONS_FETCHED => report.submissions_fetched += 1,
+            CACHE_HITS => report.cache_hits += 1,
+            RETRIES => report.retries += 1,
+            FILES_WRITTEN => report.files_written += 1,
+            _ => {}
+        }
+    }
+}
+
+impl Default for MetricsLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Marks a tracked span in the span's extensions.
+struct TrackedSpan;
+
+impl<S> Layer<S> for MetricsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let tracked = matches!(
+            attrs.metadata().name(),
+            CRAWL_PROBLEM_SPAN | POST_GRAPHQL_SPAN | FETCH_SUBMITTED_CODE_SPAN
+        );
+        if tracked && let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(TrackedSpan);
+            self.open_span(span.name());
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        if span.extensions().get::<TrackedSpan>().is_some() {
+            self.close_span(span.name());
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = CounterVisitor(None);
+        event.record(&mut visitor);
+        if let Some(counter) = visitor.0 {
+            self.record_counter(&counter);
+        }
+    }
+}
+
+/// Reads the `counter` field of an event.
+struct CounterVisitor(Option<String>);
+
+impl Visit for CounterVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "counter" {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_metrics_layer_collects_spans_and_counters() {
+        let metrics = MetricsLayer::new();
+        let subscriber = tracing_subscriber::registry().with(metrics.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let problem = tracing::info_span!(CRAWL_PROBLEM_SPAN, slug = "two-sum");
+            let _problem = problem.enter();
+            for _ in 0..2 {
+                let _request = tracing::debug_span!(POST_GRAPHQL_SPAN).entered();
+            }
+            tracing::debug_span!(FETCH_SUBMITTED_CODE_SPAN).in_scope(|| count(RETRIES));
+            count(SUBMISSIONS_FETCHED);
+            count(FILES_WRITTEN);
+        });
+
+        let report = metrics.report();
+        assert_eq!(report.problems_processed, 1);
+        assert_eq!(report.graphql_requests, 2);
+        assert_eq!(report.page_loads, 1);
+        assert_eq!(report.retries, 1);
+        assert_eq!(report.submissions_fetched, 1);
+        assert_eq!(report.files_written, 1);
+        assert_eq!(report.cache_hits, 0);
+    }
+
+    #[test]
+    fn test_concurrent_requests_are_timed_once() {
+        let metrics = MetricsLayer::new();
+        let subscriber = tracing_subscriber::registry().with(metrics.clone());
+        let pause = Duration::from_millis(50);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let first = tracing::debug_span!(POST_GRAPHQL_SPAN);
+            let second = tracing::debug_span!(POST_GRAPHQL_SPAN);
+            std::thread::sleep(pause);
+            drop((first, second));
+            tracing::debug_span!(POST_GRAPHQL_SPAN).in_scope(|| std::thread::sleep(pause));
+        });
+
+        let report = metrics.report();
+        assert_eq!(report.graphql_requests, 3);
+        assert!(report.graphql_seconds >= 0.1);
+        // Summing the spans would give at least 0.15 seconds
+        assert!(report.graphql_seconds < 0.15);
+        assert!(report.graphql_seconds <= report.total_seconds);
+    }
+}